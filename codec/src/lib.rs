@@ -0,0 +1,210 @@
+//! Decrypting and decoding the frames sent by co2 monitors, without the device
+//!
+//! This is `no_std`: no allocation, no clock and no `hidapi`. Use it to talk to a co2 monitor
+//! over a transport `co2meter-rs` doesn't support, like a USB host stack on a microcontroller.
+//! `co2meter-rs` re-exports it as its `codec` module.
+//!
+//! # Features
+//! `serde` : Enable serde Serialize and Deserialize derives for [MessageKind].
+
+#![cfg_attr(not(test), no_std)]
+
+use core::fmt;
+
+#[cfg(feature="serde")]
+use serde::{Serialize, Deserialize};
+
+/// Code of the byte that ends every frame
+pub const CODE_END_MESSAGE : u8 = 0x0D;
+/// Code of a frame holding the co2 in ppm
+pub const CODE_CO2 : u8 = 0x50;
+/// Code of a frame holding the temperature in sixteenths of a kelvin
+pub const CODE_TEMPERATURE : u8 = 0x42;
+/// Code of a frame holding the relative humidity in hundredths of a percent
+pub const CODE_HUMIDITY : u8 = 0x41;
+
+/// A decoded frame from the device
+///
+/// If you enable the `serde` feature then this also derives Serialize and Deserialize
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum MessageKind {
+    /// CO2 in ppm
+    Co2(u32),
+    /// Temperature in degrees celcius
+    Temperature(f32),
+    /// Relative humidity in percent. Only sent by some models
+    Humidity(f32),
+    /// A well formed frame with a code this crate doesn't interpret: `(code, raw value)`
+    Unknown(u8, u16),
+}
+
+/// Why a frame could not be decoded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The frame doesn't end with the end of message code followed by zeros
+    InvalidEnd,
+    /// The checksum byte of the frame doesn't match its content
+    Checksum { expected: u8, actual: u8 },
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::InvalidEnd => write!(f, "The end of the frame is not intact"),
+            DecodeError::Checksum { expected, actual } => {
+                write!(f, "Checksum mismatch: expected {:#04x} but got {:#04x}", expected, actual)
+            }
+        }
+    }
+}
+
+impl core::error::Error for DecodeError {}
+
+/// Convert a raw temperature value from the device to degrees celcius
+pub fn convert_temperature_to_celcius(temp : u16) -> f32 {
+    // goes in increments of 1/16th of a degree kelvin
+    temp as f32 * 0.0625 - 273.15
+}
+/// Convert a raw temperature value from the device to degrees fahrenheit
+pub fn convert_temperature_to_fahrenheit(temp : u16) -> f32 {
+    convert_temperature_to_celcius(temp) * 1.8 + 32.0
+}
+/// Convert a raw temperature value from the device to kelvin
+pub fn convert_temperature_to_kelvin(temp : u16) -> f32 {
+    convert_temperature_to_celcius(temp) + 273.15
+}
+/// Convert a raw temperature value from the device to tenths of a kelvin, rounded to the nearest
+/// one. Only uses integer math, for targets without floats.
+pub fn convert_temperature_to_decikelvin(temp : u16) -> u32 {
+    // sixteenths to tenths
+    (u32::from(temp) * 10 + 8) / 16
+}
+
+/// Pack a frame into a `u64`, first byte most significant. This is the order the decryption
+/// works in.
+pub fn frame_to_u64_be(frame: &[u8;8]) -> u64 {
+    u64::from_be_bytes(*frame)
+}
+/// The inverse of [frame_to_u64_be]
+pub fn u64_to_frame_be(x: u64) -> [u8;8] {
+    x.to_be_bytes()
+}
+/// Pack a frame into a `u64`, first byte least significant. For tools that store captured
+/// frames as little endian integers.
+pub fn frame_to_u64_le(frame: &[u8;8]) -> u64 {
+    u64::from_le_bytes(*frame)
+}
+/// The inverse of [frame_to_u64_le]
+pub fn u64_to_frame_le(x: u64) -> [u8;8] {
+    x.to_le_bytes()
+}
+/// Decrypt a frame as it was read from the device. 
+///
+/// `magic_table` is the table that was sent to the device when it was opened (all zeros unless
+/// you set another one with `CO2MonitorBuilder::magic_table`) and `magic_word` is usually
+/// `b"Htemp99e"`. This is what `CO2Monitor` does to every frame unless `bypass_decrypt` is set,
+/// use it to decrypt frames you captured yourself.
+pub fn decrypt_frame(data: [u8;8], magic_table: &[u8;8], magic_word: &[u8;8]) -> [u8;8] {
+    // rearrange data and turn into u64
+    let rearranged_data : [u8;8] = [
+        data[2],
+        data[4],
+        data[0],
+        data[7],
+        data[1],
+        data[6],
+        data[5],
+        data[3]
+    ];
+    let message = frame_to_u64_be(&rearranged_data);
+    // XOR with magic table
+    let mut result = message ^ frame_to_u64_be(magic_table);
+    // cyclic shift by 3 to the right
+    result = (result >> 3) | (result << 61);
+    let result_list = u64_to_frame_be(result);
+    // They really should enable the array_zip feature... Really stupid that they haven't
+    let magic_word = get_magic_word(magic_word);
+    let mut i = 0;
+    result_list.map(|r| r.wrapping_sub(magic_word[{i+=1;i-1}]))
+}
+// swap the nibbles of every byte of the magic word
+fn get_magic_word(word: &[u8;8]) -> [u8;8]{
+    let mut list = [0_u8;8];
+    let mut i = 0;
+    for byte in word {
+        list[i] = (byte << 4)  | (byte >> 4);
+        i+=1;
+    }
+    list
+}
+// the checksum a decrypted frame should carry in its 4th byte
+fn checksum(msg: &[u8;8]) -> u8 {
+    msg[0].wrapping_add(msg[1]).wrapping_add(msg[2])
+}
+
+/// Check the checksum of a decrypted frame: the 4th byte must be the wrapping sum of the first
+/// three. 
+///
+/// This doesn't look at the rest of the frame, use [decode_message] for a full check.
+pub fn verify_checksum(frame: &[u8;8]) -> bool {
+    checksum(frame) == frame[3]
+}
+
+/// Decode a decrypted frame in the layout most devices use. 
+///
+/// Checks that the frame ends with the end of message code followed by zeros and that the
+/// checksum is right. Use `FrameLayout::decode` from `co2meter-rs` for other layouts.
+pub fn decode_message(msg: &[u8;8]) -> Result<MessageKind, DecodeError> {
+    // verify end of the message is intact
+    if msg[5]!=0 || msg[6]!=0 || msg[7] !=0 || msg[4]!= CODE_END_MESSAGE{
+        return Err(DecodeError::InvalidEnd);
+    }
+    // verify checksum
+    if !verify_checksum(msg){
+        return Err(DecodeError::Checksum { expected: msg[3], actual: checksum(msg) });
+    }
+    let value : u16 = ((msg[1] as u16) << 8) | msg[2] as u16;
+    Ok(match msg[0] {
+        CODE_CO2 => MessageKind::Co2(value as u32),
+        CODE_TEMPERATURE => MessageKind::Temperature(convert_temperature_to_celcius(value)),
+        CODE_HUMIDITY => MessageKind::Humidity(value as f32 / 100.0),
+        code => MessageKind::Unknown(code, value),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_frames() {
+        // 0x03 0x20 = 800 ppm
+        assert_eq!(decode_message(&[0x50, 0x03, 0x20, 0x73, 0x0D, 0, 0, 0]), Ok(MessageKind::Co2(800)));
+        // 0x12 0xC0 = 4800 = 300K
+        match decode_message(&[0x42, 0x12, 0xC0, 0x14, 0x0D, 0, 0, 0]) {
+            Ok(MessageKind::Temperature(t)) => assert!((t - 26.85).abs() < 0.001),
+            other => panic!("unexpected {:?}", other),
+        }
+        assert_eq!(decode_message(&[0x41, 0x11, 0x94, 0xE6, 0x0D, 0, 0, 0]), Ok(MessageKind::Humidity(45.0)));
+        assert_eq!(decode_message(&[0x6D, 0x00, 0x01, 0x6E, 0x0D, 0, 0, 0]), Ok(MessageKind::Unknown(0x6D, 1)));
+    }
+
+    #[test]
+    fn checksums() {
+        assert!(verify_checksum(&[0x50, 0x03, 0x20, 0x73, 0x0D, 0, 0, 0]));
+        // 0x42 + 0xC0 + 0x60 wraps around to 0x62
+        assert!(verify_checksum(&[0x42, 0xC0, 0x60, 0x62, 0, 0, 0, 0]));
+        assert!(!verify_checksum(&[0x50, 0x03, 0x20, 0x74, 0x0D, 0, 0, 0]));
+    }
+
+    #[test]
+    fn reject_bad_frames() {
+        assert_eq!(decode_message(&[0x50, 0x03, 0x20, 0x73, 0x0D, 0, 0, 1]), Err(DecodeError::InvalidEnd));
+        assert_eq!(decode_message(&[0x50, 0x03, 0x20, 0x73, 0x0C, 0, 0, 0]), Err(DecodeError::InvalidEnd));
+        assert_eq!(
+            decode_message(&[0x50, 0x03, 0x20, 0x74, 0x0D, 0, 0, 0]),
+            Err(DecodeError::Checksum { expected: 0x74, actual: 0x73 })
+        );
+    }
+}
@@ -0,0 +1,78 @@
+//! Continuously print readings from the first co2 monitor found.
+//!
+//! Run with `cargo run --example monitor` and stop it with Ctrl-C. Set `RUST_LOG=debug` for more
+//! output.
+
+use std::error::Error;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use co2meter_rs::{CO2Monitor, CO2MonitorError, DEFAULT_MAX_REQUESTS};
+use log::{debug, error, info, warn};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+
+fn main() {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+
+    // Ctrl-C only flips this flag so that we finish the current read and let the monitor drop
+    // (which closes the device) instead of dying in the middle of a HID transfer.
+    let running = Arc::new(AtomicBool::new(true));
+    {
+        let running = Arc::clone(&running);
+        ctrlc::set_handler(move || running.store(false, Ordering::SeqCst))
+            .expect("Unable to install the Ctrl-C handler");
+    }
+
+    while running.load(Ordering::SeqCst) {
+        let mut co2 = match CO2Monitor::default() {
+            Ok(co2) => co2,
+            Err(e) if is_device_not_found(e.as_ref()) => {
+                warn!("No co2 monitor plugged in, retrying in {:?}", RETRY_DELAY);
+                sleep_while_running(&running, RETRY_DELAY);
+                continue;
+            }
+            Err(e) => {
+                error!("Unable to set up the co2 monitor: {}", e);
+                return;
+            }
+        };
+        info!("Monitoring {} ({})", co2.info().product_name, co2.info().path);
+
+        while running.load(Ordering::SeqCst) {
+            // read_data opens the device, reads until it has both values and closes it again.
+            // Reopening every poll is cheap next to a 10 second interval, it leaves the device free
+            // for other programs in between and it recovers by itself from a replug. Keep the
+            // device open yourself only if you read much more often than this.
+            match co2.read_data(DEFAULT_MAX_REQUESTS) {
+                Ok(reading) => info!("CO2: {} | Temp: {:.1}", reading.co2_ppm, reading.temp_c),
+                Err(e) => {
+                    warn!("Unable to read from the co2 monitor: {}", e);
+                    if !co2.is_device_present() {
+                        warn!("The co2 monitor was unplugged");
+                        break;
+                    }
+                }
+            }
+            sleep_while_running(&running, POLL_INTERVAL);
+        }
+    }
+    debug!("Shutting down");
+}
+
+fn is_device_not_found(e: &(dyn Error + 'static)) -> bool {
+    matches!(e.downcast_ref::<CO2MonitorError>(), Some(CO2MonitorError::DeviceNotFound))
+}
+
+// sleep in small steps so that Ctrl-C doesn't have to wait for the whole interval
+fn sleep_while_running(running: &AtomicBool, duration: Duration) {
+    let step = Duration::from_millis(100);
+    let mut slept = Duration::ZERO;
+    while slept < duration && running.load(Ordering::SeqCst) {
+        thread::sleep(step);
+        slept += step;
+    }
+}
@@ -0,0 +1,9 @@
+#![no_main]
+
+use co2meter_rs::codec::decode_message;
+use libfuzzer_sys::fuzz_target;
+
+// decoding arbitrary frames must return an error instead of panicking
+fuzz_target!(|frame: [u8; 8]| {
+    let _ = decode_message(&frame);
+});
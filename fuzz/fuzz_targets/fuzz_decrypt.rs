@@ -0,0 +1,9 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+// arbitrary frames, magic tables and magic words must never panic
+fuzz_target!(|input: ([u8; 8], [u8; 8], [u8; 8])| {
+    let (frame, magic_table, magic_word) = input;
+    co2meter_rs::decrypt_frame(frame, &magic_table, &magic_word);
+});
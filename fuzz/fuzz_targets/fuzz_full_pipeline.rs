@@ -0,0 +1,10 @@
+#![no_main]
+
+use co2meter_rs::codec::{decode_message, decrypt_frame};
+use libfuzzer_sys::fuzz_target;
+
+// what the library does to every frame it reads from the device
+fuzz_target!(|frame: [u8; 8]| {
+    let decrypted = decrypt_frame(frame, &[0; 8], b"Htemp99e");
+    let _ = decode_message(&decrypted);
+});
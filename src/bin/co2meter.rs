@@ -0,0 +1,106 @@
+//! Command line access to a co2 monitor. Built with the `cli` feature.
+//!
+//! ```text
+//! co2meter read [--json]
+//! co2meter watch [--interval SECONDS] [--json]
+//! co2meter list [--json]
+//! ```
+
+use std::env;
+use std::error::Error;
+use std::process;
+use std::thread;
+use std::time::Duration;
+
+use co2meter_rs::{CO2Monitor, CO2Reading, DEFAULT_MAX_REQUESTS};
+
+const DEFAULT_INTERVAL_SECS : u64 = 10;
+const USAGE : &str = "Usage: co2meter <read|watch|list> [--interval SECONDS] [--json]";
+
+struct Args {
+    command : String,
+    interval : Duration,
+    json : bool,
+}
+
+fn main() {
+    let args = match parse_args(env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}\n{}", e, USAGE);
+            process::exit(2);
+        }
+    };
+    let result = match args.command.as_str() {
+        "read" => read(&args),
+        "watch" => watch(&args),
+        "list" => list(&args),
+        _ => unreachable!("checked by parse_args"),
+    };
+    if let Err(e) = result {
+        eprintln!("Error: {}", e);
+        process::exit(1);
+    }
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let command = args.next().ok_or("Missing command")?;
+    if !matches!(command.as_str(), "read" | "watch" | "list") {
+        return Err(format!("Unknown command {}", command));
+    }
+    let mut parsed = Args {
+        command,
+        interval: Duration::from_secs(DEFAULT_INTERVAL_SECS),
+        json: false,
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--json" => parsed.json = true,
+            "--interval" => {
+                let secs = args.next().ok_or("--interval needs a value")?;
+                let secs : f64 = secs.parse().map_err(|_| format!("Invalid interval {}", secs))?;
+                parsed.interval = Duration::try_from_secs_f64(secs).map_err(|_| format!("Invalid interval {}", secs))?;
+            },
+            _ => return Err(format!("Unknown argument {}", arg)),
+        }
+    }
+    Ok(parsed)
+}
+
+fn print_reading(reading: &CO2Reading, json: bool) -> Result<(), Box<dyn Error>> {
+    if json {
+        println!("{}", serde_json::to_string(reading)?);
+    } else {
+        println!("{}", reading.fmt_with_locale('.', None));
+    }
+    Ok(())
+}
+
+fn read(args: &Args) -> Result<(), Box<dyn Error>> {
+    let reading = CO2Monitor::default()?.read_data(DEFAULT_MAX_REQUESTS)?;
+    print_reading(&reading, args.json)
+}
+
+fn watch(args: &Args) -> Result<(), Box<dyn Error>> {
+    let mut co2 = CO2Monitor::default()?;
+    loop {
+        match co2.read_data(DEFAULT_MAX_REQUESTS) {
+            Ok(reading) => print_reading(&reading, args.json)?,
+            // keep watching, the next read may work
+            Err(e) => eprintln!("Error: {}", e),
+        }
+        thread::sleep(args.interval);
+    }
+}
+
+fn list(args: &Args) -> Result<(), Box<dyn Error>> {
+    for co2 in CO2Monitor::find_all(false)? {
+        let info = co2.info();
+        if args.json {
+            println!("{}", serde_json::to_string(&info)?);
+        } else {
+            println!("{}  {} {}  serial {}", info.path, info.manufacturer, info.product_name, info.serial_no);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,150 @@
+use std::collections::BTreeMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[cfg(feature="serde")]
+use serde::{Serialize, Deserialize};
+
+use crate::{CO2MonitorError, CO2Reading};
+
+/// The statistics of the readings in one bucket of a [BucketAggregator]
+///
+/// If you enable the `serde` feature then this also derives Serialize and Deserialize
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CO2Bucket {
+    pub start : SystemTime,
+    pub end : SystemTime,
+    pub mean_co2 : f64,
+    pub min_co2 : u32,
+    pub max_co2 : u32,
+    pub mean_temp : f64,
+    pub sample_count : usize,
+}
+
+// running totals of a bucket that is still being filled
+#[derive(Debug, Clone)]
+struct BucketTotals {
+    co2_sum : f64,
+    min_co2 : u32,
+    max_co2 : u32,
+    temp_sum : f64,
+    sample_count : usize,
+}
+
+/// Groups readings into buckets of a fixed length, like 5 minute averages for a dashboard. 
+///
+/// Readings don't carry a timestamp, so [push](BucketAggregator::push) puts them into the bucket
+/// of the current time. The buckets start at multiples of `bucket_size` since the unix epoch, so
+/// 5 minute buckets start at :00, :05, :10 and so on.
+///
+/// ```ignore
+/// let mut aggregator = BucketAggregator::new(Duration::from_secs(300))?;
+/// loop {
+///     aggregator.push(co2.read_data(50)?);
+///     for bucket in aggregator.drain_completed_buckets() {
+///         println!("{:.0} ppm over {} readings", bucket.mean_co2, bucket.sample_count);
+///     }
+///     thread::sleep(Duration::from_secs(10));
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct BucketAggregator {
+    bucket_size : Duration,
+    // keyed by the number of buckets between the epoch and the start of the bucket
+    buckets : BTreeMap<u128, BucketTotals>,
+}
+
+impl BucketAggregator {
+    /// Fails with [CO2MonitorError::InvalidArgument] if `bucket_size` is under a millisecond
+    pub fn new(bucket_size: Duration) -> Result<BucketAggregator, CO2MonitorError> {
+        if bucket_size.as_millis() == 0 {
+            return Err(CO2MonitorError::InvalidArgument("`bucket_size` must be at least a millisecond".to_owned()));
+        }
+        Ok(BucketAggregator {
+            bucket_size,
+            buckets: BTreeMap::new(),
+        })
+    }
+    /// Add a reading to the bucket of the current time
+    pub fn push(&mut self, reading: CO2Reading) {
+        self.push_at(reading, SystemTime::now());
+    }
+    /// Add a reading to the bucket of `time`, for readings you stamped yourself
+    pub fn push_at(&mut self, reading: CO2Reading, time: SystemTime) {
+        let co2 = reading.co2_ppm.get();
+        let temp = reading.temp_c.get() as f64;
+        let totals = self.buckets.entry(self.bucket_index(time)).or_insert(BucketTotals {
+            co2_sum: 0.0,
+            min_co2: co2,
+            max_co2: co2,
+            temp_sum: 0.0,
+            sample_count: 0,
+        });
+        totals.co2_sum += co2 as f64;
+        totals.min_co2 = totals.min_co2.min(co2);
+        totals.max_co2 = totals.max_co2.max(co2);
+        totals.temp_sum += temp;
+        totals.sample_count += 1;
+    }
+    /// Remove and return the buckets that ended by now, oldest first
+    pub fn drain_completed_buckets(&mut self) -> Vec<CO2Bucket> {
+        let current = self.bucket_index(SystemTime::now());
+        let still_open = self.buckets.split_off(&current);
+        let completed = std::mem::replace(&mut self.buckets, still_open);
+        completed.into_iter()
+            .map(|(index, totals)| {
+                let start = self.bucket_start(index);
+                CO2Bucket {
+                    start,
+                    end: start + self.bucket_size,
+                    mean_co2: totals.co2_sum / totals.sample_count as f64,
+                    min_co2: totals.min_co2,
+                    max_co2: totals.max_co2,
+                    mean_temp: totals.temp_sum / totals.sample_count as f64,
+                    sample_count: totals.sample_count,
+                }
+            })
+            .collect()
+    }
+    // times before the epoch go into the first bucket
+    fn bucket_index(&self, time: SystemTime) -> u128 {
+        let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+        since_epoch.as_millis() / self.bucket_size.as_millis()
+    }
+    fn bucket_start(&self, index: u128) -> SystemTime {
+        UNIX_EPOCH + Duration::from_millis((index * self.bucket_size.as_millis()) as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reading;
+
+    #[test]
+    fn buckets() {
+        assert!(BucketAggregator::new(Duration::ZERO).is_err());
+        let mut aggregator = BucketAggregator::new(Duration::from_secs(300)).unwrap();
+        let at = |secs: u64| UNIX_EPOCH + Duration::from_secs(secs);
+        aggregator.push_at(reading(800, 21.0), at(600));
+        aggregator.push_at(reading(900, 22.0), at(899));
+        aggregator.push_at(reading(1000, 23.0), at(900));
+        aggregator.push_at(reading(400, 20.0), SystemTime::now() + Duration::from_secs(3600));
+        let buckets = aggregator.drain_completed_buckets();
+        assert_eq!(buckets.len(), 2);
+        assert_eq!(buckets[0], CO2Bucket {
+            start: at(600),
+            end: at(900),
+            mean_co2: 850.0,
+            min_co2: 800,
+            max_co2: 900,
+            mean_temp: 21.5,
+            sample_count: 2,
+        });
+        assert_eq!(buckets[1].start, at(900));
+        assert_eq!(buckets[1].sample_count, 1);
+        // a bucket that isn't over yet stays
+        assert!(aggregator.drain_completed_buckets().is_empty());
+        assert_eq!(aggregator.buckets.len(), 1);
+    }
+}
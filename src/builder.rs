@@ -0,0 +1,192 @@
+use std::error::Error;
+use std::time::Duration;
+
+#[cfg(feature="regex")]
+use regex::Regex;
+
+use crate::{CO2Monitor, FrameLayout};
+use crate::message::CustomDecoder;
+use crate::CO2MonitorError;
+
+/// Build a [CO2Monitor] with more settings than [CO2Monitor::new] takes. 
+///
+/// Every setting defaults to the behaviour of [CO2Monitor::default].
+///
+/// ```ignore
+/// let mut co2 = CO2MonitorBuilder::new()
+///     .interface_path("1-13:1.0")
+///     .warmup_frames(3)
+///     .build()?;
+/// let result = co2.read_data(50)?;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CO2MonitorBuilder {
+    bypass_decrypt : bool,
+    interface_path : Option<String>,
+    #[cfg(feature="regex")]
+    path_pattern : Option<String>,
+    warmup_frames : u32,
+    temp_offset_c : f32,
+    co2_offset_ppm : i32,
+    magic_table : Option<[u8;8]>,
+    magic_word : Option<[u8;8]>,
+    skip_magic_table : bool,
+    report_id_prefix : bool,
+    frame_layout : FrameLayout,
+    read_interval : Option<Duration>,
+    decoder : Option<CustomDecoder>,
+}
+
+// longest read_interval the builder accepts. The device sends a frame about every second, so
+// anything above this is most likely a mistake
+const MAX_READ_INTERVAL : Duration = Duration::from_secs(30);
+
+impl CO2MonitorBuilder {
+    pub fn new() -> CO2MonitorBuilder {
+        CO2MonitorBuilder::default()
+    }
+    /// Skip decryption. See [CO2Monitor::new]
+    pub fn bypass_decrypt(mut self, bypass_decrypt: bool) -> CO2MonitorBuilder {
+        self.bypass_decrypt = bypass_decrypt;
+        self
+    }
+    /// Use the co2 monitor at this interface path instead of the first one found. See
+    /// [CO2Monitor::new]
+    pub fn interface_path(mut self, interface_path: impl Into<String>) -> CO2MonitorBuilder {
+        self.interface_path = Some(interface_path.into());
+        self
+    }
+    /// Use the first co2 monitor whose interface path matches this regex, like `"1-[0-9]+:1\\.0"`
+    /// for any port on one USB controller. Takes precedence over
+    /// [interface_path](CO2MonitorBuilder::interface_path). 
+    ///
+    /// Needs the `regex` feature. An invalid pattern makes [build](CO2MonitorBuilder::build) fail.
+    #[cfg(feature="regex")]
+    pub fn path_pattern(mut self, pattern: &str) -> CO2MonitorBuilder {
+        self.path_pattern = Some(pattern.to_owned());
+        self
+    }
+    /// Number of frames to read and throw away every time the device is opened, before the
+    /// reading starts. The first frames after opening can be stale and give bogus values. These
+    /// frames don't count towards `max_requests`. Defaults to 0. If the device is already open
+    /// when reading starts, the queued frames are thrown away with [CO2Monitor::flush] instead.
+    pub fn warmup_frames(mut self, warmup_frames: u32) -> CO2MonitorBuilder {
+        self.warmup_frames = warmup_frames;
+        self
+    }
+    /// Added to every temperature read from the device, to correct a meter that reads too high
+    /// or too low. If yours reads 1.5°C high, use `-1.5`. Defaults to 0. 
+    pub fn temp_offset_c(mut self, temp_offset_c: f32) -> CO2MonitorBuilder {
+        self.temp_offset_c = temp_offset_c;
+        self
+    }
+    /// Added to every co2 value read from the device, to correct baseline drift. Defaults to 0.
+    pub fn co2_offset_ppm(mut self, co2_offset_ppm: i32) -> CO2MonitorBuilder {
+        self.co2_offset_ppm = co2_offset_ppm;
+        self
+    }
+    /// The table sent to the device when it is opened, which it then uses to encrypt the frames.
+    /// Defaults to all zeros. 
+    pub fn magic_table(mut self, magic_table: [u8;8]) -> CO2MonitorBuilder {
+        self.magic_table = Some(magic_table);
+        self
+    }
+    /// The word used to decrypt the frames. Defaults to `b"Htemp99e"`, some clones use another
+    /// one. 
+    pub fn magic_word(mut self, magic_word: [u8;8]) -> CO2MonitorBuilder {
+        self.magic_word = Some(magic_word);
+        self
+    }
+    /// Whether to send the [magic table](CO2MonitorBuilder::magic_table) to the device every
+    /// time it is opened. Defaults to `true`. 
+    ///
+    /// Sending it is a feature report, which some systems don't allow even though reading works.
+    /// With `false` the device keeps using the table it was last sent, or its own default after
+    /// it was plugged in, and the frames only decrypt correctly if that is the same as
+    /// [magic_table](CO2MonitorBuilder::magic_table). If the readings come out as garbage, try
+    /// [bypass_decrypt](CO2MonitorBuilder::bypass_decrypt) as well.
+    pub fn send_magic_table(mut self, send_magic_table: bool) -> CO2MonitorBuilder {
+        self.skip_magic_table = !send_magic_table;
+        self
+    }
+    /// Read 9 bytes per frame and drop the first one. Some devices put a report id in front of
+    /// every frame, which shifts the payload by one byte and breaks decoding. Defaults to
+    /// `false`. 
+    pub fn report_id_prefix(mut self, report_id_prefix: bool) -> CO2MonitorBuilder {
+        self.report_id_prefix = report_id_prefix;
+        self
+    }
+    /// Where the code, value, checksum and end of message code are in a decrypted frame, for
+    /// firmware that orders them differently. Defaults to [FrameLayout::default].
+    pub fn frame_layout(mut self, frame_layout: FrameLayout) -> CO2MonitorBuilder {
+        self.frame_layout = frame_layout;
+        self
+    }
+    /// Sleep this long between two frames while reading, instead of reading them as fast as the
+    /// device sends them. Defaults to no sleep. More than 30 seconds makes
+    /// [build](CO2MonitorBuilder::build) fail. 
+    pub fn read_interval(mut self, read_interval: Duration) -> CO2MonitorBuilder {
+        self.read_interval = Some(read_interval);
+        self
+    }
+    /// Replace the built-in decoding of frames for devices this crate doesn't know about. 
+    ///
+    /// `decoder` gets every frame after decryption (unless `bypass_decrypt` is set) and returns
+    /// the co2 in ppm and the temperature in degrees celcius it found in it, if any. The offsets
+    /// are still applied afterwards. It is used by every read, only
+    /// [health_check](CO2Monitor::health_check) looks at the frames themselves. The humidity is
+    /// never read with a custom decoder, so [read_full](CO2Monitor::read_full) always fails
+    /// with one.
+    pub fn decoder(mut self, decoder: impl Fn([u8;8]) -> (Option<u32>, Option<f32>) + Send + Sync + 'static) -> CO2MonitorBuilder {
+        self.decoder = Some(CustomDecoder::new(decoder));
+        self
+    }
+    /// Find the device and create the [CO2Monitor]
+    pub fn build(self) -> Result<CO2Monitor, Box<dyn Error>> {
+        if self.read_interval.is_some_and(|interval| interval > MAX_READ_INTERVAL) {
+            return Err(CO2MonitorError::InvalidArgument(format!("`read_interval` can't be more than {:?}", MAX_READ_INTERVAL)).into());
+        }
+        let mut co2 = self.find()?;
+        co2.warmup_frames = self.warmup_frames;
+        co2.temp_offset_c = self.temp_offset_c;
+        co2.co2_offset_ppm = self.co2_offset_ppm;
+        if let Some(magic_table) = self.magic_table {
+            co2.magic_table = magic_table;
+        }
+        if let Some(magic_word) = self.magic_word {
+            co2.magic_word = magic_word;
+        }
+        co2.send_magic_table = !self.skip_magic_table;
+        co2.report_id_prefix = self.report_id_prefix;
+        co2.frame_layout = self.frame_layout;
+        co2.read_interval = self.read_interval;
+        co2.decoder = self.decoder;
+        Ok(co2)
+    }
+    #[cfg(feature="regex")]
+    fn find(&self) -> Result<CO2Monitor, Box<dyn Error>> {
+        match &self.path_pattern {
+            Some(pattern) => {
+                let pattern = Regex::new(pattern)
+                    .map_err(|e| CO2MonitorError::InvalidArgument(e.to_string()))?;
+                CO2Monitor::find_by_path_pattern(&pattern, self.bypass_decrypt)
+            },
+            None => CO2Monitor::new(self.bypass_decrypt, self.interface_path.clone()),
+        }
+    }
+    #[cfg(not(feature="regex"))]
+    fn find(&self) -> Result<CO2Monitor, Box<dyn Error>> {
+        CO2Monitor::new(self.bypass_decrypt, self.interface_path.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_long_read_interval() {
+        let err = CO2MonitorBuilder::new().read_interval(Duration::from_secs(31)).build().err().unwrap();
+        assert!(matches!(err.downcast_ref::<CO2MonitorError>(), Some(CO2MonitorError::InvalidArgument(_))));
+    }
+}
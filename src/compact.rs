@@ -0,0 +1,80 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Serialize, Deserialize};
+
+use crate::{CO2MonitorError, CO2Reading, CO2PPM, TempCelsius};
+
+// what a CompactReading is serialized as: [unix_ts, co2_ppm, temp_c * 100]
+type CompactTuple = (u64, u32, i32);
+
+/// A [CO2Reading] with a timestamp that serializes as a 3 element array
+/// `[unix_ts, co2_ppm, temp_c_times_100]`, for links where every byte counts (like MQTT over a
+/// slow radio). 
+///
+/// `{"co2_ppm":812,"temp_c":21.3125}` becomes `[1700000000,812,2131]` with `serde_json`. The
+/// temperature is rounded to 0.01°C and the humidity is left out. Only available with the
+/// `serde` feature.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "CompactTuple", into = "CompactTuple")]
+pub struct CompactReading {
+    /// Seconds since the unix epoch
+    pub unix_ts : u64,
+    pub reading : CO2Reading,
+}
+
+impl CompactReading {
+    /// Stamp `reading` with the current time
+    pub fn now(reading: CO2Reading) -> CompactReading {
+        // the clock is only before the epoch if it is badly wrong
+        let unix_ts = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        CompactReading { unix_ts, reading }
+    }
+}
+
+impl From<CompactReading> for CompactTuple {
+    fn from(compact: CompactReading) -> Self {
+        let temp = (compact.reading.temp_c.get() * 100.0).round() as i32;
+        (compact.unix_ts, compact.reading.co2_ppm.get(), temp)
+    }
+}
+
+impl TryFrom<CompactTuple> for CompactReading {
+    type Error = CO2MonitorError;
+
+    fn try_from((unix_ts, co2_ppm, temp): CompactTuple) -> Result<Self, Self::Error> {
+        Ok(CompactReading {
+            unix_ts,
+            reading: CO2Reading {
+                co2_ppm: CO2PPM::try_from(co2_ppm)?,
+                temp_c: TempCelsius::try_from(temp as f32 / 100.0)?,
+                humidity_pct: None,
+            },
+        })
+    }
+}
+
+impl From<CompactReading> for CO2Reading {
+    fn from(compact: CompactReading) -> Self {
+        compact.reading
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let reading = CO2Reading { humidity_pct: Some(45.0), ..crate::reading(812, 21.3125) };
+        let json = serde_json::to_string(&CompactReading { unix_ts: 1_700_000_000, reading }).unwrap();
+        assert_eq!(json, "[1700000000,812,2131]");
+        let compact : CompactReading = serde_json::from_str(&json).unwrap();
+        assert_eq!(compact.unix_ts, 1_700_000_000);
+        let reading = CO2Reading::from(compact);
+        assert_eq!(reading.co2_ppm.get(), 812);
+        assert!((reading.temp_c.get() - 21.31).abs() < 0.001);
+        assert!(reading.humidity_pct.is_none());
+        assert!(serde_json::from_str::<CompactReading>("[0,2000000,2131]").is_err());
+        assert!(serde_json::from_str::<CompactReading>("[0,812,-30000]").is_err());
+    }
+}
@@ -0,0 +1,49 @@
+use std::time::Duration;
+
+#[cfg(feature="serde")]
+use serde::{Serialize, Deserialize};
+
+/// Timings of the last [CO2Monitor::read_data](crate::CO2Monitor::read_data), see
+/// [CO2Monitor::last_read_diagnostics](crate::CO2Monitor::last_read_diagnostics)
+///
+/// Use this to tell a slow device (or USB hub) from slow software: the device sends a frame
+/// roughly every second, so a `mean_read_duration` far above that points at the hardware.
+///
+/// If you enable the `serde` feature then this also derives Serialize and Deserialize
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ReadDiagnostics {
+    /// Time spent opening the device and sending it the magic table. Zero if it was already open
+    pub open_duration : Duration,
+    /// Frames read from the device, including the warmup frames
+    pub read_count : u32,
+    /// Time from the start of the read until the device was closed again
+    pub total_duration : Duration,
+}
+
+impl ReadDiagnostics {
+    /// The average time one frame took, leaving out the time spent opening the device. `None`
+    /// if no frame was read.
+    pub fn mean_read_duration(&self) -> Option<Duration> {
+        if self.read_count == 0 {
+            return None;
+        }
+        Some(self.total_duration.saturating_sub(self.open_duration) / self.read_count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mean_read_duration() {
+        let diagnostics = ReadDiagnostics {
+            open_duration: Duration::from_millis(200),
+            read_count: 4,
+            total_duration: Duration::from_millis(4200),
+        };
+        assert_eq!(diagnostics.mean_read_duration(), Some(Duration::from_secs(1)));
+        assert_eq!(ReadDiagnostics::default().mean_read_duration(), None);
+    }
+}
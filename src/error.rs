@@ -0,0 +1,155 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+use hidapi::HidError;
+
+/// Errors produced by this crate
+#[derive(Debug)]
+pub enum CO2MonitorError {
+    /// No co2 monitor matching the request is plugged in
+    DeviceNotFound,
+    /// There are co2 monitors plugged in, but none at the requested interface path
+    PathNotFound(String),
+    /// Tried to use the connection to the device while it is closed
+    NotOpen,
+    /// A value could not be turned into a valid [CO2Reading](crate::CO2Reading)
+    InvalidReading(String),
+    /// Writing out a reading failed
+    Io(io::Error),
+    /// An argument passed to this crate was rejected
+    InvalidArgument(String),
+    /// Talking to the device failed. Holds the message of the underlying error
+    ReadFailed(String),
+    /// The read was cancelled, see [CO2Monitor::read_data_cancellable](crate::CO2Monitor::read_data_cancellable)
+    Cancelled,
+    /// hidapi failed. The [HidError] is also the [source](Error::source) of this error
+    HidError(HidError),
+    /// The device sent a frame shorter than 8 bytes. Holds the number of bytes that arrived
+    ShortRead(usize),
+    /// Another process holds the lock on the device, see
+    /// [CO2Monitor::open_exclusive](crate::CO2Monitor::open_exclusive)
+    DeviceLocked { lock_path: PathBuf },
+}
+
+impl fmt::Display for CO2MonitorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CO2MonitorError::DeviceNotFound => write!(f, "Unable to find the hid device"),
+            CO2MonitorError::PathNotFound(path) => write!(f, "No co2 monitor at the interface path {}", path),
+            CO2MonitorError::NotOpen => write!(f, "The device is not open"),
+            CO2MonitorError::InvalidReading(reason) => write!(f, "Invalid reading: {}", reason),
+            CO2MonitorError::Io(e) => write!(f, "IO error: {}", e),
+            CO2MonitorError::InvalidArgument(reason) => write!(f, "Invalid argument: {}", reason),
+            CO2MonitorError::ReadFailed(reason) => write!(f, "Reading from the device failed: {}", reason),
+            CO2MonitorError::Cancelled => write!(f, "The read was cancelled"),
+            CO2MonitorError::HidError(e) => write!(f, "HID error: {}", e),
+            CO2MonitorError::ShortRead(len) => write!(f, "Only got {} of the 8 bytes of a frame", len),
+            CO2MonitorError::DeviceLocked { lock_path } => write!(f, "The device is locked by another process ({})", lock_path.display()),
+        }
+    }
+}
+
+impl Error for CO2MonitorError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CO2MonitorError::Io(e) => Some(e),
+            CO2MonitorError::HidError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl CO2MonitorError {
+    // turn the boxed errors of the device methods into a CO2MonitorError, keeping it if it
+    // already is one
+    pub(crate) fn from_boxed(e: Box<dyn Error>) -> CO2MonitorError {
+        let e = match e.downcast::<CO2MonitorError>() {
+            Ok(e) => return *e,
+            Err(e) => e,
+        };
+        let e = match e.downcast::<HidError>() {
+            Ok(e) => return CO2MonitorError::HidError(*e),
+            Err(e) => e,
+        };
+        match e.downcast::<io::Error>() {
+            Ok(e) => CO2MonitorError::Io(*e),
+            Err(e) => CO2MonitorError::ReadFailed(e.to_string()),
+        }
+    }
+}
+
+impl From<io::Error> for CO2MonitorError {
+    fn from(e: io::Error) -> Self {
+        CO2MonitorError::Io(e)
+    }
+}
+
+impl From<HidError> for CO2MonitorError {
+    fn from(e: HidError) -> Self {
+        CO2MonitorError::HidError(e)
+    }
+}
+
+/// For using `?` on the `Box<dyn Error>` results of [CO2Monitor](crate::CO2Monitor) in a function
+/// that returns a [CO2MonitorError]. Errors that are neither a [CO2MonitorError], a [HidError]
+/// nor an [io::Error] become [CO2MonitorError::ReadFailed].
+impl From<Box<dyn Error>> for CO2MonitorError {
+    fn from(e: Box<dyn Error>) -> Self {
+        CO2MonitorError::from_boxed(e)
+    }
+}
+
+/// A best effort mapping to an [io::ErrorKind]. The [CO2MonitorError] is kept as the inner error,
+/// except for [CO2MonitorError::Io] which is unwrapped.
+impl From<CO2MonitorError> for io::Error {
+    fn from(e: CO2MonitorError) -> Self {
+        let kind = match e {
+            CO2MonitorError::Io(e) => return e,
+            CO2MonitorError::DeviceNotFound | CO2MonitorError::PathNotFound(_) => io::ErrorKind::NotFound,
+            CO2MonitorError::NotOpen => io::ErrorKind::NotConnected,
+            CO2MonitorError::InvalidReading(_) => io::ErrorKind::InvalidData,
+            CO2MonitorError::InvalidArgument(_) => io::ErrorKind::InvalidInput,
+            CO2MonitorError::Cancelled => io::ErrorKind::Interrupted,
+            CO2MonitorError::ShortRead(_) => io::ErrorKind::UnexpectedEof,
+            CO2MonitorError::DeviceLocked { .. } => io::ErrorKind::WouldBlock,
+            CO2MonitorError::ReadFailed(_) | CO2MonitorError::HidError(_) => io::ErrorKind::Other,
+        };
+        io::Error::new(kind, e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn source_chain() {
+        let hid_error : Box<dyn Error> = Box::new(HidError::HidApiError { message: "device disconnected".to_owned() });
+        let e = CO2MonitorError::from_boxed(hid_error);
+        assert!(matches!(e, CO2MonitorError::HidError(_)));
+        let source = e.source().unwrap();
+        assert!(source.downcast_ref::<HidError>().is_some());
+        assert_eq!(e.to_string(), format!("HID error: {}", source));
+        let io_error : Box<dyn Error> = Box::new(io::Error::new(io::ErrorKind::BrokenPipe, "gone"));
+        assert!(CO2MonitorError::from_boxed(io_error).source().unwrap().downcast_ref::<io::Error>().is_some());
+        let e = CO2MonitorError::from_boxed("no co2".into());
+        assert!(matches!(e, CO2MonitorError::ReadFailed(_)));
+        assert!(e.source().is_none());
+        assert!(CO2MonitorError::Cancelled.source().is_none());
+    }
+
+    #[test]
+    fn into_io_error() {
+        let e = io::Error::from(CO2MonitorError::DeviceNotFound);
+        assert_eq!(e.kind(), io::ErrorKind::NotFound);
+        assert!(e.get_ref().unwrap().downcast_ref::<CO2MonitorError>().is_some());
+        let e = io::Error::from(CO2MonitorError::Io(io::Error::new(io::ErrorKind::BrokenPipe, "gone")));
+        assert_eq!(e.kind(), io::ErrorKind::BrokenPipe);
+        assert_eq!(e.to_string(), "gone");
+        let e : CO2MonitorError = HidError::InitializationError.into();
+        assert_eq!(io::Error::from(e).kind(), io::ErrorKind::Other);
+        assert_eq!(io::Error::from(CO2MonitorError::ShortRead(3)).kind(), io::ErrorKind::UnexpectedEof);
+    }
+}
@@ -0,0 +1,116 @@
+use std::fs::{File, OpenOptions};
+use std::ops::{Deref, DerefMut};
+use std::path::{Path, PathBuf};
+
+use fs2::FileExt;
+
+use crate::{CO2Monitor, CO2MonitorError};
+
+// where the lock files go if it exists, the temp dir otherwise
+const LOCK_DIR : &str = "/run/lock";
+
+/// A [CO2Monitor] that no other process can use at the same time, see
+/// [CO2Monitor::open_exclusive]
+///
+/// Use it like the [CO2Monitor] it wraps. The lock is released when the guard is dropped, or when
+/// the process exits.
+///
+/// ```ignore
+/// let mut co2 = CO2Monitor::default()?.open_exclusive()?;
+/// dbg!(co2.read_data(50)?);
+/// ```
+pub struct CO2MonitorGuard {
+    monitor : CO2Monitor,
+    // holding the file holds the lock
+    _lock : File,
+    lock_path : PathBuf,
+}
+
+impl CO2MonitorGuard {
+    pub(crate) fn lock(monitor: CO2Monitor) -> Result<CO2MonitorGuard, CO2MonitorError> {
+        let info = monitor.get_info_borrowed();
+        // many meters share a serial number like "1.40", so it alone doesn't tell them apart
+        let lock_path = lock_dir().join(lock_file_name(info.path, info.serial_no));
+        let lock = lock_file(&lock_path)?;
+        Ok(CO2MonitorGuard { monitor, _lock: lock, lock_path })
+    }
+    /// The lock file this guard holds
+    pub fn lock_path(&self) -> &Path {
+        &self.lock_path
+    }
+    /// Release the lock and give back the monitor
+    pub fn into_inner(self) -> CO2Monitor {
+        self.monitor
+    }
+}
+
+impl Deref for CO2MonitorGuard {
+    type Target = CO2Monitor;
+
+    fn deref(&self) -> &CO2Monitor {
+        &self.monitor
+    }
+}
+
+impl DerefMut for CO2MonitorGuard {
+    fn deref_mut(&mut self) -> &mut CO2Monitor {
+        &mut self.monitor
+    }
+}
+
+// open and lock the file at `lock_path` without waiting, creating it if needed
+fn lock_file(lock_path: &Path) -> Result<File, CO2MonitorError> {
+    let lock = OpenOptions::new().create(true).truncate(false).write(true).open(lock_path)?;
+    match lock.try_lock_exclusive() {
+        Ok(()) => Ok(lock),
+        Err(e) if e.raw_os_error() == fs2::lock_contended_error().raw_os_error() => {
+            Err(CO2MonitorError::DeviceLocked { lock_path: lock_path.to_path_buf() })
+        },
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn lock_dir() -> PathBuf {
+    let dir = Path::new(LOCK_DIR);
+    if dir.is_dir() {
+        dir.to_path_buf()
+    } else {
+        std::env::temp_dir()
+    }
+}
+
+// `co2monitor-{path}-{serial_no}.lock` with everything but letters, digits, `-` and `.`
+// replaced, as interface paths contain slashes. The serial number is left out if it is empty
+fn lock_file_name(path: &str, serial_no: &str) -> String {
+    let id = if serial_no.is_empty() { path.to_owned() } else { format!("{}-{}", path, serial_no) };
+    let id : String = id.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '.' { c } else { '_' })
+        .collect();
+    format!("co2monitor-{}.lock", id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lock_file_names() {
+        assert_eq!(lock_file_name("/dev/hidraw0", "1.40"), "co2monitor-_dev_hidraw0-1.40.lock");
+        assert_eq!(lock_file_name("1-13:1.0", ""), "co2monitor-1-13_1.0.lock");
+        // two meters with the same serial number on different ports
+        assert_ne!(lock_file_name("1-13:1.0", "1.40"), lock_file_name("1-14:1.0", "1.40"));
+    }
+
+    #[test]
+    fn lock_is_exclusive() {
+        let lock_path = std::env::temp_dir().join(format!("co2monitor-test-{}.lock", std::process::id()));
+        let lock = lock_file(&lock_path).unwrap();
+        match lock_file(&lock_path) {
+            Err(CO2MonitorError::DeviceLocked { lock_path: locked }) => assert_eq!(locked, lock_path),
+            other => panic!("unexpected {:?}", other.map(|_| ())),
+        }
+        drop(lock);
+        assert!(lock_file(&lock_path).is_ok());
+        let _ = std::fs::remove_file(&lock_path);
+    }
+}
@@ -0,0 +1,228 @@
+use std::collections::VecDeque;
+
+use crate::CO2Reading;
+
+/// Post-processing for readings before you log them or alert on them. 
+///
+/// Returns `None` to drop a reading, or the reading to pass on, possibly changed. Filters can be
+/// combined with [chain](CO2ReadingFilter::chain).
+///
+/// ```ignore
+/// let mut filter = RangeFilter::new(300, 5000, -10.0, 50.0).chain(MovingAverageFilter::new(5));
+/// loop {
+///     if let Some(reading) = filter.filter(co2.read_data(50)?) {
+///         log(reading);
+///     }
+/// }
+/// ```
+pub trait CO2ReadingFilter {
+    fn filter(&mut self, reading: CO2Reading) -> Option<CO2Reading>;
+    /// Run `next` on the readings this filter passes on
+    fn chain<F: CO2ReadingFilter>(self, next: F) -> FilterChain<Self, F>
+    where
+        Self: Sized,
+    {
+        FilterChain { first: self, second: next }
+    }
+}
+
+/// Two filters run one after the other, see [CO2ReadingFilter::chain]
+#[derive(Debug, Clone)]
+pub struct FilterChain<A, B> {
+    first : A,
+    second : B,
+}
+
+impl<A: CO2ReadingFilter, B: CO2ReadingFilter> CO2ReadingFilter for FilterChain<A, B> {
+    fn filter(&mut self, reading: CO2Reading) -> Option<CO2Reading> {
+        self.first.filter(reading).and_then(|reading| self.second.filter(reading))
+    }
+}
+
+/// Passes every reading on unchanged
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PassThroughFilter;
+
+impl CO2ReadingFilter for PassThroughFilter {
+    fn filter(&mut self, reading: CO2Reading) -> Option<CO2Reading> {
+        Some(reading)
+    }
+}
+
+/// Drops readings whose co2 or temperature is outside a range, for example the glitches some
+/// devices send right after they are plugged in. The limits are inclusive.
+#[derive(Debug, Clone)]
+pub struct RangeFilter {
+    min_co2_ppm : u32,
+    max_co2_ppm : u32,
+    min_temp_c : f32,
+    max_temp_c : f32,
+}
+
+impl RangeFilter {
+    pub fn new(min_co2_ppm: u32, max_co2_ppm: u32, min_temp_c: f32, max_temp_c: f32) -> RangeFilter {
+        RangeFilter {
+            min_co2_ppm,
+            max_co2_ppm,
+            min_temp_c,
+            max_temp_c,
+        }
+    }
+}
+
+impl CO2ReadingFilter for RangeFilter {
+    fn filter(&mut self, reading: CO2Reading) -> Option<CO2Reading> {
+        let co2_ok = (self.min_co2_ppm..=self.max_co2_ppm).contains(&reading.co2_ppm.get());
+        let temp_ok = (self.min_temp_c..=self.max_temp_c).contains(&reading.temp_c.get());
+        (co2_ok && temp_ok).then_some(reading)
+    }
+}
+
+/// Replaces every reading by the [average](CO2Reading::average) of the last `window` readings,
+/// including itself. Until `window` readings were seen it averages the ones it has.
+#[derive(Debug, Clone)]
+pub struct MovingAverageFilter {
+    window : usize,
+    readings : VecDeque<CO2Reading>,
+}
+
+impl MovingAverageFilter {
+    /// A `window` of 0 is treated as 1
+    pub fn new(window: usize) -> MovingAverageFilter {
+        let window = window.max(1);
+        MovingAverageFilter {
+            window,
+            readings: VecDeque::with_capacity(window),
+        }
+    }
+}
+
+impl CO2ReadingFilter for MovingAverageFilter {
+    fn filter(&mut self, reading: CO2Reading) -> Option<CO2Reading> {
+        if self.readings.len() == self.window {
+            self.readings.pop_front();
+        }
+        self.readings.push_back(reading);
+        CO2Reading::average(self.readings.make_contiguous())
+    }
+}
+
+/// Drops readings whose co2 is within `tolerance_ppm` of the reading right before it. 
+///
+/// Unlike [ChangeFilter] this compares against the previous reading even if that one was dropped,
+/// so a slow drift is dropped as well.
+#[derive(Debug, Clone)]
+pub struct DeduplicationFilter {
+    tolerance_ppm : u32,
+    previous_co2_ppm : Option<u32>,
+}
+
+impl DeduplicationFilter {
+    pub fn new(tolerance_ppm: u32) -> DeduplicationFilter {
+        DeduplicationFilter {
+            tolerance_ppm,
+            previous_co2_ppm: None,
+        }
+    }
+}
+
+impl CO2ReadingFilter for DeduplicationFilter {
+    fn filter(&mut self, reading: CO2Reading) -> Option<CO2Reading> {
+        let co2_ppm = reading.co2_ppm.get();
+        let previous = self.previous_co2_ppm.replace(co2_ppm);
+        match previous {
+            Some(previous) if co2_ppm.abs_diff(previous) <= self.tolerance_ppm => None,
+            _ => Some(reading),
+        }
+    }
+}
+
+/// Drops readings whose co2 didn't change much. 
+///
+/// Handy for event driven logging: only readings whose co2 differs by more than `ppm_threshold`
+/// from the last reading that was let through are returned. The first reading is always returned.
+///
+/// ```ignore
+/// let mut filter = ChangeFilter::new(50);
+/// loop {
+///     if let Some(reading) = filter.push(co2.read_data(50)?) {
+///         log(reading);
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ChangeFilter {
+    ppm_threshold : u32,
+    last_co2_ppm : Option<u32>,
+}
+
+impl ChangeFilter {
+    pub fn new(ppm_threshold: u32) -> ChangeFilter {
+        ChangeFilter {
+            ppm_threshold,
+            last_co2_ppm: None,
+        }
+    }
+    /// Returns `Some(reading)` if it should be recorded
+    pub fn push(&mut self, reading: CO2Reading) -> Option<CO2Reading> {
+        let co2_ppm = reading.co2_ppm.get();
+        if let Some(last) = self.last_co2_ppm {
+            if co2_ppm.abs_diff(last) <= self.ppm_threshold {
+                return None;
+            }
+        }
+        self.last_co2_ppm = Some(co2_ppm);
+        Some(reading)
+    }
+}
+
+impl CO2ReadingFilter for ChangeFilter {
+    fn filter(&mut self, reading: CO2Reading) -> Option<CO2Reading> {
+        self.push(reading)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reading;
+
+    #[test]
+    fn only_changes_pass() {
+        let mut filter = ChangeFilter::new(50);
+        let passed : Vec<u32> = [800, 820, 850, 851, 900, 860, 800]
+            .into_iter()
+            .filter_map(|ppm| filter.push(reading(ppm, 20.0)))
+            .map(|r| r.co2_ppm.get())
+            .collect();
+        // compared against the last reading that passed, not the last one pushed
+        assert_eq!(passed, vec![800, 851, 800]);
+    }
+
+    fn run(filter: &mut impl CO2ReadingFilter, ppms: &[u32]) -> Vec<u32> {
+        ppms.iter()
+            .filter_map(|&ppm| filter.filter(reading(ppm, 20.0)))
+            .map(|r| r.co2_ppm.get())
+            .collect()
+    }
+
+    #[test]
+    fn built_in_filters() {
+        assert_eq!(run(&mut PassThroughFilter, &[800, 800]), vec![800, 800]);
+        assert_eq!(run(&mut RangeFilter::new(300, 5000, -10.0, 50.0), &[0, 300, 5000, 5001]), vec![300, 5000]);
+        assert!(RangeFilter::new(300, 5000, -10.0, 10.0).filter(reading(800, 20.0)).is_none());
+        assert_eq!(run(&mut MovingAverageFilter::new(2), &[800, 900, 1000, 1000]), vec![800, 850, 950, 1000]);
+        assert_eq!(run(&mut MovingAverageFilter::new(0), &[800, 900]), vec![800, 900]);
+        // compared against the previous reading, so the drift from 800 to 860 is dropped
+        assert_eq!(run(&mut DeduplicationFilter::new(20), &[800, 820, 840, 860, 900]), vec![800, 900]);
+    }
+
+    #[test]
+    fn chained_filters() {
+        let mut filter = RangeFilter::new(300, 5000, -10.0, 50.0)
+            .chain(MovingAverageFilter::new(2))
+            .chain(DeduplicationFilter::new(10));
+        // 9000 never reaches the average
+        assert_eq!(run(&mut filter, &[800, 9000, 810, 1000]), vec![800, 905]);
+    }
+}
@@ -0,0 +1,54 @@
+use crate::CO2Reading;
+
+/// How [CO2Monitor::read_and_log](crate::CO2Monitor::read_and_log) writes a reading
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// `CO2: 812 ppm | Temp: 21.3°C`, see [CO2Reading::fmt_with_locale]
+    Human,
+    /// `{"co2_ppm":812,"temp_c":21.3125}`, see [CO2Reading::to_json_string]
+    Json,
+    /// `812,21.3125,` with the columns `co2_ppm,temp_c,humidity_pct`. The humidity is empty if
+    /// there is none
+    Csv,
+    /// `812 21.3125`, the values separated by spaces with the humidity added at the end if there
+    /// is one. Easy to pick apart with `cut` or `awk`
+    Machine,
+}
+
+impl OutputFormat {
+    /// The reading in this format, without a trailing newline
+    pub fn format(&self, reading: &CO2Reading) -> String {
+        let co2 = reading.co2_ppm.get();
+        let temp = reading.temp_c.get();
+        match self {
+            OutputFormat::Human => reading.fmt_with_locale('.', None),
+            OutputFormat::Json => reading.to_json_string(),
+            OutputFormat::Csv => match reading.humidity_pct {
+                Some(humidity_pct) => format!("{},{},{}", co2, temp, humidity_pct),
+                None => format!("{},{},", co2, temp),
+            },
+            OutputFormat::Machine => match reading.humidity_pct {
+                Some(humidity_pct) => format!("{} {} {}", co2, temp, humidity_pct),
+                None => format!("{} {}", co2, temp),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reading;
+
+    #[test]
+    fn formats() {
+        let r = reading(812, 21.3125);
+        assert_eq!(OutputFormat::Human.format(&r), "CO2: 812 ppm | Temp: 21.3°C");
+        assert_eq!(OutputFormat::Json.format(&r), "{\"co2_ppm\":812,\"temp_c\":21.3125}");
+        assert_eq!(OutputFormat::Csv.format(&r), "812,21.3125,");
+        assert_eq!(OutputFormat::Machine.format(&r), "812 21.3125");
+        let r = CO2Reading { humidity_pct: Some(45.5), ..r };
+        assert_eq!(OutputFormat::Csv.format(&r), "812,21.3125,45.5");
+        assert_eq!(OutputFormat::Machine.format(&r), "812 21.3125 45.5");
+    }
+}
@@ -0,0 +1,101 @@
+#[cfg(feature="serde")]
+use serde::{Serialize, Deserialize};
+
+use crate::codec::{decode_message, DecodeError, MessageKind};
+
+/// What [CO2Monitor::health_check](crate::CO2Monitor::health_check) saw in a batch of frames
+///
+/// The statistics are `None` if no valid frame of that kind was seen.
+///
+/// If you enable the `serde` feature then this also derives Serialize and Deserialize
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CO2MonitorHealthReport {
+    pub total_frames : u32,
+    pub valid_co2_frames : u32,
+    pub valid_temp_frames : u32,
+    pub checksum_errors : u32,
+    /// Frames that don't end with the end of message code followed by zeros
+    pub invalid_end_errors : u32,
+    /// Codes of well formed frames that this crate doesn't interpret, sorted and without
+    /// duplicates
+    pub unknown_codes : Vec<u8>,
+    pub co2_min : Option<u32>,
+    pub co2_max : Option<u32>,
+    pub co2_mean : Option<f64>,
+    pub temp_min : Option<f32>,
+    pub temp_max : Option<f32>,
+    /// Whether the frames decode better with decryption than without
+    pub encryption_detected : bool,
+}
+
+impl CO2MonitorHealthReport {
+    // Build the report from already decrypted (or plain) frames
+    pub(crate) fn from_frames(frames: &[[u8;8]], encryption_detected: bool) -> CO2MonitorHealthReport {
+        let mut co2_values : Vec<u32> = vec![];
+        let mut temp_values : Vec<f32> = vec![];
+        let mut checksum_errors = 0;
+        let mut invalid_end_errors = 0;
+        let mut unknown_codes : Vec<u8> = vec![];
+        for frame in frames {
+            match decode_message(frame) {
+                Ok(MessageKind::Co2(co2)) => co2_values.push(co2),
+                Ok(MessageKind::Temperature(temp)) => temp_values.push(temp),
+                Ok(MessageKind::Humidity(_)) => {},
+                Ok(MessageKind::Unknown(code, _)) => unknown_codes.push(code),
+                Err(DecodeError::Checksum { .. }) => checksum_errors += 1,
+                Err(DecodeError::InvalidEnd) => invalid_end_errors += 1,
+            }
+        }
+        unknown_codes.sort_unstable();
+        unknown_codes.dedup();
+        CO2MonitorHealthReport {
+            total_frames: frames.len() as u32,
+            valid_co2_frames: co2_values.len() as u32,
+            valid_temp_frames: temp_values.len() as u32,
+            checksum_errors,
+            invalid_end_errors,
+            unknown_codes,
+            co2_min: co2_values.iter().copied().min(),
+            co2_max: co2_values.iter().copied().max(),
+            co2_mean: if co2_values.is_empty() {
+                None
+            } else {
+                Some(co2_values.iter().map(|co2| *co2 as f64).sum::<f64>() / co2_values.len() as f64)
+            },
+            temp_min: temp_values.iter().copied().reduce(f32::min),
+            temp_max: temp_values.iter().copied().reduce(f32::max),
+            encryption_detected,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_from_frames() {
+        let frames = [
+            [0x50, 0x03, 0x20, 0x73, 0x0D, 0, 0, 0], // 800 ppm
+            [0x50, 0x03, 0x84, 0xD7, 0x0D, 0, 0, 0], // 900 ppm
+            [0x42, 0x12, 0xC0, 0x14, 0x0D, 0, 0, 0], // 26.85°C
+            [0x50, 0x03, 0x20, 0x74, 0x0D, 0, 0, 0], // bad checksum
+            [0x50, 0x03, 0x20, 0x73, 0x0C, 0, 0, 0], // bad end
+            [0x6D, 0x00, 0x01, 0x6E, 0x0D, 0, 0, 0],
+            [0x6D, 0x00, 0x02, 0x6F, 0x0D, 0, 0, 0],
+        ];
+        let report = CO2MonitorHealthReport::from_frames(&frames, true);
+        assert_eq!(report.total_frames, 7);
+        assert_eq!(report.valid_co2_frames, 2);
+        assert_eq!(report.valid_temp_frames, 1);
+        assert_eq!(report.checksum_errors, 1);
+        assert_eq!(report.invalid_end_errors, 1);
+        assert_eq!(report.unknown_codes, vec![0x6D]);
+        assert_eq!((report.co2_min, report.co2_max, report.co2_mean), (Some(800), Some(900), Some(850.0)));
+        assert_eq!(report.temp_min, report.temp_max);
+        assert!(report.encryption_detected);
+        let empty = CO2MonitorHealthReport::from_frames(&[], false);
+        assert_eq!((empty.co2_mean, empty.temp_min), (None, None));
+    }
+}
@@ -0,0 +1,130 @@
+use std::collections::VecDeque;
+
+/// The last `capacity` co2 values, with some statistics over them.
+///
+/// Pushing is O(1): once the history is full the oldest value is dropped. The statistics are
+/// computed from scratch on every call, which is fine for a few thousand values.
+///
+/// ```ignore
+/// let mut history = CO2PPMHistory::new(360);
+/// loop {
+///     history.push(co2.read_data(50)?.co2_ppm.get());
+///     println!("p95 over the last hour: {:?}", history.percentile(95.0));
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct CO2PPMHistory {
+    capacity : usize,
+    values : VecDeque<u32>,
+}
+
+impl CO2PPMHistory {
+    /// A history of at most `capacity` values. A capacity of 0 keeps nothing.
+    pub fn new(capacity: usize) -> CO2PPMHistory {
+        CO2PPMHistory {
+            capacity,
+            values: VecDeque::with_capacity(capacity),
+        }
+    }
+    /// Add a value, dropping the oldest one if the history is full
+    pub fn push(&mut self, ppm: u32) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.values.len() == self.capacity {
+            self.values.pop_front();
+        }
+        self.values.push_back(ppm);
+    }
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+    /// The values from oldest to newest
+    pub fn iter(&self) -> impl Iterator<Item = u32> + '_ {
+        self.values.iter().copied()
+    }
+    pub fn mean(&self) -> Option<f64> {
+        if self.values.is_empty() {
+            return None;
+        }
+        let sum : f64 = self.values.iter().map(|v| *v as f64).sum();
+        Some(sum / self.values.len() as f64)
+    }
+    pub fn min(&self) -> Option<u32> {
+        self.values.iter().copied().min()
+    }
+    pub fn max(&self) -> Option<u32> {
+        self.values.iter().copied().max()
+    }
+    /// The population standard deviation
+    pub fn stddev(&self) -> Option<f64> {
+        let mean = self.mean()?;
+        let variance : f64 = self.values.iter()
+            .map(|v| (*v as f64 - mean).powi(2))
+            .sum::<f64>() / self.values.len() as f64;
+        Some(variance.sqrt())
+    }
+    /// The 50th percentile, see [CO2PPMHistory::percentile]. Unlike the mean it isn't thrown off
+    /// by the odd bad value.
+    pub fn median(&self) -> Option<f64> {
+        self.percentile(50.0)
+    }
+    /// The `p`th percentile (0 to 100), interpolating linearly between the two closest values.
+    ///
+    /// Returns `None` if the history is empty or `p` is out of range.
+    pub fn percentile(&self, p: f64) -> Option<f64> {
+        if self.values.is_empty() || !(0.0..=100.0).contains(&p) {
+            return None;
+        }
+        let mut sorted : Vec<u32> = self.values.iter().copied().collect();
+        sorted.sort_unstable();
+        let rank = p / 100.0 * (sorted.len() - 1) as f64;
+        let lower = rank.floor() as usize;
+        let upper = rank.ceil() as usize;
+        let fraction = rank - lower as f64;
+        Some(sorted[lower] as f64 + (sorted[upper] as f64 - sorted[lower] as f64) * fraction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evicts_oldest() {
+        let mut history = CO2PPMHistory::new(3);
+        for ppm in [400, 500, 600, 700] {
+            history.push(ppm);
+        }
+        assert_eq!(history.iter().collect::<Vec<u32>>(), vec![500, 600, 700]);
+        let mut empty = CO2PPMHistory::new(0);
+        empty.push(400);
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn statistics() {
+        let mut history = CO2PPMHistory::new(10);
+        assert_eq!(history.mean(), None);
+        assert_eq!(history.percentile(50.0), None);
+        for ppm in [800, 400, 600, 1000] {
+            history.push(ppm);
+        }
+        assert_eq!(history.mean(), Some(700.0));
+        assert_eq!(history.min(), Some(400));
+        assert_eq!(history.max(), Some(1000));
+        assert!((history.stddev().unwrap() - 223.606_797_75).abs() < 1e-6);
+        assert_eq!(history.percentile(0.0), Some(400.0));
+        assert_eq!(history.percentile(50.0), Some(700.0));
+        assert_eq!(history.percentile(100.0), Some(1000.0));
+        assert_eq!(history.percentile(101.0), None);
+        assert_eq!(history.median(), Some(700.0));
+        let mut single = CO2PPMHistory::new(10);
+        single.push(450);
+        assert_eq!(single.median(), Some(450.0));
+        assert_eq!(single.percentile(95.0), Some(450.0));
+    }
+}
@@ -0,0 +1,148 @@
+//! Serve the latest reading over HTTP, for dashboards that poll a JSON endpoint
+//!
+//! Enabled with the `http` feature. A [CO2HttpServer] reads the device on one thread and answers
+//! requests on another from the last successful reading, so requests never wait for the device:
+//! - `GET /reading` returns the reading as JSON like `{"co2_ppm":812,"temp_c":21.3125}`, or
+//!   `503` if there was no successful reading yet
+//! - `GET /health` returns `200` if the last successful reading is at most two poll intervals
+//!   old and `503` otherwise
+//!
+//! ```ignore
+//! let server = CO2HttpServer::new(CO2Monitor::default()?, "0.0.0.0:8080".parse()?, Duration::from_secs(10))?;
+//! // curl localhost:8080/reading
+//! ```
+
+use std::io;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use tiny_http::{Header, Method, Response, Server};
+
+use crate::{CO2Monitor, CO2MonitorError, DEFAULT_MAX_REQUESTS};
+
+// how often the http thread checks whether it should stop
+const STOP_POLL_INTERVAL : Duration = Duration::from_millis(100);
+
+// the last successful reading, shared between the two threads
+#[derive(Debug, Default)]
+struct LatestReading {
+    json : Option<String>,
+    read_at : Option<Instant>,
+}
+
+/// Reads a [CO2Monitor] on a background thread and serves the latest reading over HTTP
+pub struct CO2HttpServer {
+    addr : SocketAddr,
+    stop : Arc<AtomicBool>,
+    reader : JoinHandle<()>,
+    responder : JoinHandle<()>,
+}
+
+impl CO2HttpServer {
+    /// Bind `bind_addr` and start reading the device every `poll_interval`. 
+    ///
+    /// Fails if the address can't be bound. Use port 0 to let the OS pick one, see
+    /// [CO2HttpServer::local_addr].
+    pub fn new(monitor: CO2Monitor, bind_addr: SocketAddr, poll_interval: Duration) -> Result<CO2HttpServer, CO2MonitorError> {
+        let server = Server::http(bind_addr).map_err(io::Error::other)?;
+        let addr = server.server_addr().to_ip()
+            .ok_or_else(|| CO2MonitorError::InvalidArgument(format!("{} is not an IP address", bind_addr)))?;
+        let stop = Arc::new(AtomicBool::new(false));
+        let latest = Arc::new(Mutex::new(LatestReading::default()));
+        let reader = {
+            let stop = Arc::clone(&stop);
+            let latest = Arc::clone(&latest);
+            thread::spawn(move || read_loop(monitor, poll_interval, &latest, &stop))
+        };
+        let responder = {
+            let stop = Arc::clone(&stop);
+            thread::spawn(move || respond_loop(server, poll_interval, &latest, &stop))
+        };
+        Ok(CO2HttpServer { addr, stop, reader, responder })
+    }
+    /// The address the server is listening on
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+    /// Stop both threads and wait for them to exit. This waits for the current reading to finish
+    pub fn stop(self) {
+        self.stop.store(true, Ordering::SeqCst);
+        // the threads only panic if the lock is poisoned, and nothing panics while holding it
+        let _ = self.responder.join();
+        let _ = self.reader.join();
+    }
+}
+
+fn read_loop(mut monitor: CO2Monitor, poll_interval: Duration, latest: &Mutex<LatestReading>, stop: &AtomicBool) {
+    while !stop.load(Ordering::SeqCst) {
+        if let Ok(reading) = monitor.read_data(DEFAULT_MAX_REQUESTS) {
+            let mut latest = latest.lock().expect("The latest reading lock is poisoned");
+            latest.json = Some(reading.to_json_string());
+            latest.read_at = Some(Instant::now());
+        }
+        thread::sleep(poll_interval);
+    }
+}
+
+fn respond_loop(server: Server, poll_interval: Duration, latest: &Mutex<LatestReading>, stop: &AtomicBool) {
+    while !stop.load(Ordering::SeqCst) {
+        let request = match server.recv_timeout(STOP_POLL_INTERVAL) {
+            Ok(Some(request)) => request,
+            Ok(None) | Err(_) => continue,
+        };
+        let (status, body) = {
+            let latest = latest.lock().expect("The latest reading lock is poisoned");
+            route(request.method(), request.url(), &latest, poll_interval, Instant::now())
+        };
+        let content_type = if status == 200 && body.starts_with('{') { "application/json" } else { "text/plain" };
+        let header = Header::from_bytes(&b"Content-Type"[..], content_type.as_bytes()).expect("valid header");
+        // the client going away is not our problem
+        let _ = request.respond(Response::from_string(body).with_status_code(status).with_header(header));
+    }
+}
+
+// the status code and body for a request
+fn route(method: &Method, url: &str, latest: &LatestReading, poll_interval: Duration, now: Instant) -> (u16, String) {
+    let path = url.split('?').next().unwrap_or(url);
+    if path != "/reading" && path != "/health" {
+        return (404, "Not found".to_owned());
+    }
+    if *method != Method::Get {
+        return (405, "Only GET is supported".to_owned());
+    }
+    if path == "/reading" {
+        return match &latest.json {
+            Some(json) => (200, json.clone()),
+            None => (503, "No reading yet".to_owned()),
+        };
+    }
+    match latest.read_at {
+        Some(read_at) if now.duration_since(read_at) <= poll_interval * 2 => (200, "OK".to_owned()),
+        _ => (503, "No recent reading".to_owned()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn routes() {
+        let interval = Duration::from_secs(10);
+        let start = Instant::now();
+        let mut latest = LatestReading::default();
+        assert_eq!(route(&Method::Get, "/reading", &latest, interval, start).0, 503);
+        assert_eq!(route(&Method::Get, "/health", &latest, interval, start).0, 503);
+        latest.json = Some("{\"co2_ppm\":812,\"temp_c\":21.3125}".to_owned());
+        latest.read_at = Some(start);
+        assert_eq!(route(&Method::Get, "/reading?pretty", &latest, interval, start),
+            (200, "{\"co2_ppm\":812,\"temp_c\":21.3125}".to_owned()));
+        assert_eq!(route(&Method::Get, "/health", &latest, interval, start + Duration::from_secs(20)).0, 200);
+        assert_eq!(route(&Method::Get, "/health", &latest, interval, start + Duration::from_secs(21)).0, 503);
+        assert_eq!(route(&Method::Post, "/reading", &latest, interval, start).0, 405);
+        assert_eq!(route(&Method::Get, "/", &latest, interval, start).0, 404);
+    }
+}
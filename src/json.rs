@@ -0,0 +1,79 @@
+//! Conversions between [CO2Reading] and [serde_json::Value]
+//!
+//! Enabled with the `json` feature. Unlike the `serde` feature this does not add any derives to
+//! [CO2Reading], which is handy if you already build your JSON documents by hand.
+
+use serde_json::{json, Value};
+
+use crate::{CO2MonitorError, CO2Reading, CO2PPM, TempCelsius};
+
+/// Turn a [CO2Reading] into a JSON object like `{"co2_ppm": 800, "temp_c": 21.5}`
+///
+/// `humidity_pct` is only added if the reading has one.
+pub fn reading_to_value(r: &CO2Reading) -> Value {
+    let mut value = json!({
+        "co2_ppm": r.co2_ppm.get(),
+        "temp_c": r.temp_c.get(),
+    });
+    if let Some(humidity_pct) = r.humidity_pct {
+        value["humidity_pct"] = json!(humidity_pct);
+    }
+    value
+}
+
+/// Parse a JSON object (as produced by [reading_to_value]) back into a [CO2Reading]
+pub fn value_to_reading(v: &Value) -> Result<CO2Reading, CO2MonitorError> {
+    let co2_ppm = v.get("co2_ppm")
+        .and_then(Value::as_u64)
+        .and_then(|ppm| u32::try_from(ppm).ok())
+        .ok_or_else(|| CO2MonitorError::InvalidReading("`co2_ppm` is missing or not a u32".to_owned()))?;
+    let temp_c = v.get("temp_c")
+        .and_then(Value::as_f64)
+        .ok_or_else(|| CO2MonitorError::InvalidReading("`temp_c` is missing or not a number".to_owned()))?;
+    let humidity_pct = match v.get("humidity_pct") {
+        None | Some(Value::Null) => None,
+        Some(humidity_pct) => Some(humidity_pct.as_f64()
+            .ok_or_else(|| CO2MonitorError::InvalidReading("`humidity_pct` is not a number".to_owned()))? as f32),
+    };
+    Ok(CO2Reading {
+        co2_ppm: CO2PPM::try_from(co2_ppm)?,
+        temp_c: TempCelsius::try_from(temp_c as f32)?,
+        humidity_pct,
+    })
+}
+
+impl From<CO2Reading> for Value {
+    fn from(r: CO2Reading) -> Self {
+        reading_to_value(&r)
+    }
+}
+
+impl TryFrom<&Value> for CO2Reading {
+    type Error = CO2MonitorError;
+
+    fn try_from(v: &Value) -> Result<Self, Self::Error> {
+        value_to_reading(v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let reading = CO2Reading { humidity_pct: Some(45.0), ..crate::reading(812, 21.3) };
+        let value = Value::from(reading.clone());
+        assert_eq!(value["co2_ppm"], 812);
+        let parsed = value_to_reading(&value).unwrap();
+        assert_eq!(parsed.co2_ppm, reading.co2_ppm);
+        assert_eq!(parsed.temp_c, reading.temp_c);
+        assert_eq!(parsed.humidity_pct, reading.humidity_pct);
+    }
+
+    #[test]
+    fn rejects_missing_fields() {
+        assert!(value_to_reading(&json!({"co2_ppm": 812})).is_err());
+        assert!(value_to_reading(&json!({"co2_ppm": -1, "temp_c": 20.0})).is_err());
+    }
+}
@@ -0,0 +1,67 @@
+use std::io::Write;
+
+use crate::{CO2MonitorError, CO2Reading};
+
+/// Writes readings as newline delimited JSON (JSON Lines). 
+///
+/// Every reading becomes one JSON object on its own line and the writer is flushed after each
+/// one, so tools tailing the output (Filebeat, `jq`, ...) see readings as they come in. Wrap a
+/// `File`, `Stdout`, `TcpStream` or any other [Write].
+///
+/// ```ignore
+/// let mut out = CO2JsonLinesWriter::new(std::io::stdout());
+/// out.write_reading(&co2.read_data(50)?)?;
+/// ```
+#[derive(Debug)]
+pub struct CO2JsonLinesWriter<W: Write> {
+    writer : W,
+}
+
+impl<W: Write> CO2JsonLinesWriter<W> {
+    pub fn new(writer: W) -> CO2JsonLinesWriter<W> {
+        CO2JsonLinesWriter { writer }
+    }
+    /// Write one reading followed by `\n` and flush. 
+    ///
+    /// The whole line is handed to the underlying writer in a single `write_all` call, so a line
+    /// is never split across two writes of this struct.
+    pub fn write_reading(&mut self, reading: &CO2Reading) -> Result<(), CO2MonitorError> {
+        let mut line = to_json(reading)?;
+        line.push('\n');
+        self.writer.write_all(line.as_bytes())?;
+        self.writer.flush()?;
+        Ok(())
+    }
+    /// Get the wrapped writer back
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+#[cfg(feature="serde")]
+fn to_json(reading: &CO2Reading) -> Result<String, CO2MonitorError> {
+    Ok(serde_json::to_string(reading).map_err(std::io::Error::from)?)
+}
+
+#[cfg(not(feature="serde"))]
+fn to_json(reading: &CO2Reading) -> Result<String, CO2MonitorError> {
+    Ok(reading.to_json_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reading;
+
+    #[test]
+    fn one_reading_per_line() {
+        let mut out = CO2JsonLinesWriter::new(Vec::new());
+        for ppm in [800, 801] {
+            out.write_reading(&reading(ppm, 21.5)).unwrap();
+        }
+        assert_eq!(
+            String::from_utf8(out.into_inner()).unwrap(),
+            "{\"co2_ppm\":800,\"temp_c\":21.5}\n{\"co2_ppm\":801,\"temp_c\":21.5}\n"
+        );
+    }
+}
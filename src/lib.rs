@@ -6,8 +6,47 @@
 //! other python version like homekit integration and a display server. There is no reason to add
 //! this. 
 //!
+//! Decrypting and decoding the frames is in [codec], which is the `no_std` `co2meter-codec`
+//! crate. Depend on that one alone if you don't go through `hidapi`.
+//!
 //! # Features
-//! `serde` : Enable serde Serialize and Deserialze derives for [CO2Reading] and [CO2MonitorInfo]
+//! `serde` : Enable serde Serialize and Deserialze derives for [CO2Reading] and [CO2MonitorInfo].
+//! [CO2JsonLinesWriter] also uses `serde_json` instead of [CO2Reading::to_json_string] then, and
+//! [CompactReading] is added for a smaller serialized form.
+//!
+//! `json` : Enable the [json] module to convert [CO2Reading] to and from a `serde_json::Value`
+//! without needing the `serde` feature
+//!
+//! `regex` : Enable [CO2MonitorBuilder::path_pattern] to pick the device by a regex on its
+//! interface path
+//!
+//! `cli` : Build the `co2meter` binary (`co2meter read`, `co2meter watch --interval 5` and
+//! `co2meter list`, each with an optional `--json`). Implies `serde`.
+//!
+//! `server` : Enable the [server] module to share one device with other processes over a Unix
+//! socket. Implies `json`. Only available on Unix.
+//!
+//! `mqtt` : Enable the [mqtt] module to publish readings to an MQTT broker. Implies `serde`.
+//!
+//! `http` : Enable the [http] module to serve the latest reading as JSON over HTTP.
+//!
+//! `color` : Enable [CO2Reading::fmt_colored] to print readings to a terminal with colors.
+//!
+//! `cbor` : Enable [CO2Reading::to_cbor_bytes] and [CO2Reading::from_cbor_bytes].
+//!
+//! The HID backend is picked with the same features as the `hidapi` crate, so you don't need to
+//! depend on it yourself. The default is `linux-static-libusb` (and `illumos-static-libusb`). To
+//! use another one turn off the default features and enable exactly one for your OS:
+//! - `linux-static-hidraw` / `linux-shared-hidraw` : go through the kernel's hidraw driver. Pick
+//!   this if libusb can't open the device without root, as hidraw devices can be given to a user
+//!   with a udev rule and it doesn't detach the kernel driver.
+//! - `linux-static-libusb` / `linux-shared-libusb` : go through libusb, statically or dynamically
+//!   linked.
+//! - `illumos-static-libusb` / `illumos-shared-libusb` : the same for illumos.
+//!
+//! ```toml
+//! co2meter-rs = { version = "0.1", default-features = false, features = ["linux-static-hidraw"] }
+//! ```
 //! 
 //! # Getting Started
 //!
@@ -33,59 +72,220 @@
 //! ```
 //!
 
+use std::collections::VecDeque;
 use std::error::Error;
+use std::ffi::CStr;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use hidapi::{HidApi, DeviceInfo, HidDevice};
+use log::warn;
+
+mod error;
+pub use error::CO2MonitorError;
+mod units;
+pub use units::{CO2PPM, TempCelsius};
+mod builder;
+pub use builder::CO2MonitorBuilder;
+mod message;
+pub use co2meter_codec as codec;
+pub use codec::{MessageKind, DecodeError, verify_checksum, decode_message, decrypt_frame};
+pub use codec::{frame_to_u64_be, u64_to_frame_be, frame_to_u64_le, u64_to_frame_le};
+pub use codec::{convert_temperature_to_fahrenheit, convert_temperature_to_kelvin, convert_temperature_to_decikelvin};
+pub use message::FrameLayout;
+use message::CustomDecoder;
+mod filter;
+pub use filter::{ChangeFilter, CO2ReadingFilter, FilterChain, PassThroughFilter, RangeFilter, MovingAverageFilter, DeduplicationFilter};
+mod history;
+pub use history::CO2PPMHistory;
+mod health;
+pub use health::CO2MonitorHealthReport;
+mod diagnostics;
+pub use diagnostics::ReadDiagnostics;
+mod format;
+pub use format::OutputFormat;
+mod bucket;
+pub use bucket::{BucketAggregator, CO2Bucket};
+mod statsd;
+pub use statsd::CO2MonitorStatsdEmitter;
+mod session;
+pub use session::CO2MonitorSession;
+mod exclusive;
+pub use exclusive::CO2MonitorGuard;
+mod readings;
+pub use readings::CO2MonitorReadings;
+mod jsonl;
+pub use jsonl::CO2JsonLinesWriter;
+#[cfg(feature="serde")]
+mod compact;
+#[cfg(feature="serde")]
+pub use compact::CompactReading;
+mod watcher;
+pub use watcher::{CO2MonitorWatcher, WatcherConfig, ThresholdStatus, ThresholdEvent, ThresholdWatcher, ThresholdCrossing};
+
+#[cfg(feature="json")]
+pub mod json;
+#[cfg(all(feature="server", unix))]
+pub mod server;
+#[cfg(feature="mqtt")]
+pub mod mqtt;
+#[cfg(feature="http")]
+pub mod http;
 
 #[cfg(feature="serde")]
 use serde::{Serialize, Deserialize};
 
 const CO2MON_HID_VENDOR_ID : u16 = 0x04d9;
 const CO2MON_HID_PRODUCT_ID : u16 = 0xa052;
-const CO2MON_MAGIC_WORD :  &str = "Htemp99e";
-// CO2MON magic table?
-//
-const CODE_END_MESSAGE : u8 = 0x0D;
-const CODE_CO2 : u8 = 0x50;
-const CODE_TEMPERATURE : u8 = 0x42;
+/// The `(vendor id, product id)` of the co2 monitors known to speak this protocol. Devices with
+/// one of these are picked up when looking for a co2 monitor. Add more with
+/// [register_known_device].
+pub const KNOWN_DEVICES : &[(u16, u16)] = &[(CO2MON_HID_VENDOR_ID, CO2MON_HID_PRODUCT_ID)];
+// added with register_known_device()
+static REGISTERED_DEVICES : Mutex<Vec<(u16, u16)>> = Mutex::new(Vec::new());
+const CO2MON_MAGIC_WORD :  &[u8;8] = b"Htemp99e";
+/// The recommended `max_requests` for [CO2Monitor::read_data] and the other reads
+pub const DEFAULT_MAX_REQUESTS : u32 = 50;
+// most frames CO2Monitor::flush() throws away in one call
+const MAX_FLUSH_FRAMES : u32 = 64;
+// how often a read waiting out its read_interval checks if it was cancelled
+const CANCEL_POLL_INTERVAL : Duration = Duration::from_millis(100);
+// number of frames read by CO2Monitor::auto() to decide if decryption is needed
+const PROBE_FRAMES : u32 = 16;
+// used for converting ppm to mg/m³
+const CO2_MOLAR_MASS_G_PER_MOL : f64 = 44.01;
+const GAS_CONSTANT_J_PER_MOL_K : f64 = 8.314_462_618;
+// stored by CO2Reading::to_bytes when there is no timestamp
+const NO_TIMESTAMP : u32 = 0xFFFF_FFFF;
+// how far the weights given to CO2Reading::weighted_average may be off from 1.0
+const WEIGHT_SUM_TOLERANCE : f64 = 1e-6;
+// keys of the CBOR map written by CO2Reading::to_cbor_bytes(). 0 is left for a timestamp
+#[cfg(feature="cbor")]
+const CBOR_KEY_CO2 : u8 = 1;
+#[cfg(feature="cbor")]
+const CBOR_KEY_TEMP : u8 = 2;
+#[cfg(feature="cbor")]
+const CBOR_KEY_HUMIDITY : u8 = 3;
 
+// Magnus formula coefficients for the dew point (Sonntag 1990)
+const MAGNUS_A : f32 = 17.62;
+const MAGNUS_B_C : f32 = 243.12;
+// International Standard Atmosphere, pressure ratio = (1 - ISA_LAPSE_PER_M * h)^ISA_EXPONENT
+const ISA_LAPSE_PER_M : f64 = 2.25577e-5;
+const ISA_EXPONENT : f64 = 5.25588;
 
-fn convert_temperature_to_celcius(temp : u16) -> f32 {
-    // goes in increments of 1/16th of a degree kelvin
-    temp as f32 * 0.0625 - 273.15
+/// Also look for co2 monitors with this vendor and product id, for a clone that speaks the same
+/// protocol but isn't in [KNOWN_DEVICES]. Applies to every [CO2Monitor] created afterwards.
+pub fn register_known_device(vendor_id: u16, product_id: u16) {
+    // the list is always valid, even if a thread panicked while holding the lock
+    let mut registered = REGISTERED_DEVICES.lock().unwrap_or_else(|e| e.into_inner());
+    if !registered.contains(&(vendor_id, product_id)) {
+        registered.push((vendor_id, product_id));
+    }
+}
+/// [KNOWN_DEVICES] and the ids added with [register_known_device]
+pub fn known_devices() -> Vec<(u16, u16)> {
+    let registered = REGISTERED_DEVICES.lock().unwrap_or_else(|e| e.into_inner());
+    KNOWN_DEVICES.iter().chain(registered.iter()).copied().collect()
+}
+// check the vendor and product id of a HID device
+fn is_co2_monitor(device: &DeviceInfo) -> bool {
+    DeviceIds::from(device).is_co2_monitor()
+}
+// the parts of a DeviceInfo that tell devices apart. CO2Monitor::find_device searches over these so
+// that the tests can give it made up devices
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct DeviceIds<'a> {
+    vendor_id : u16,
+    product_id : u16,
+    path : &'a CStr,
+    serial : Option<&'a str>,
 }
 
-fn list_to_u64(x: &[u8]) -> u64 {
-    x[7] as u64 +
-    ((x[6] as u64) << 8) +
-    ((x[5] as u64) << 16) +
-    ((x[4] as u64) << 24) +
-    ((x[3] as u64) << 32) +
-    ((x[2] as u64) << 40) +
-    ((x[1] as u64) << 48) +
-    ((x[0] as u64) << 56) 
+impl DeviceIds<'_> {
+    fn is_co2_monitor(&self) -> bool {
+        known_devices().contains(&(self.vendor_id, self.product_id))
+    }
 }
-fn u64_to_list(x:u64)->[u8;8]{
-    let mut list = [0_u8;8];
-    list[0] = ((x >> 56) & 0xFF) as u8; 
-    list[1] = ((x >> 48) & 0xFF) as u8; 
-    list[2] = ((x >> 40) & 0xFF) as u8; 
-    list[3] = ((x >> 32) & 0xFF) as u8; 
-    list[4] = ((x >> 24) & 0xFF) as u8; 
-    list[5] = ((x >> 16) & 0xFF) as u8; 
-    list[6] = ((x >> 8) & 0xFF) as u8; 
-    list[7] = (x & 0xFF) as u8; 
-    list
+
+impl<'a> From<&'a DeviceInfo> for DeviceIds<'a> {
+    fn from(device: &'a DeviceInfo) -> Self {
+        DeviceIds {
+            vendor_id: device.vendor_id(),
+            product_id: device.product_id(),
+            path: device.path(),
+            serial: device.serial_number(),
+        }
+    }
+}
+
+impl<'a> From<&'a DeviceIds<'a>> for DeviceIds<'a> {
+    fn from(ids: &'a DeviceIds<'a>) -> Self {
+        *ids
+    }
 }
-fn get_magic_word() -> [u8;8]{
-    let mut list = [0_u8;8];
-    let bytes = CO2MON_MAGIC_WORD.bytes();
-    let mut i = 0;
-    for byte in bytes {
-        list[i] = (byte << 4)  | (byte >> 4);
-        i+=1;
-    }
-    list
+// log what was enumerated, for when the co2 monitor wasn't found
+fn log_enumerated_devices(hid: &HidApi) {
+    let seen : Vec<String> = hid.device_list()
+        .map(|device| format!("{:04x}:{:04x}", device.vendor_id(), device.product_id()))
+        .collect();
+    let matching = hid.device_list().filter(|device| is_co2_monitor(device)).count();
+    let known : Vec<String> = known_devices().iter()
+        .map(|(vendor_id, product_id)| format!("{:04x}:{:04x}", vendor_id, product_id))
+        .collect();
+    warn!("Enumerated {} HID devices, {} of them with a co2 monitor id ({}). Seen: [{}]",
+        seen.len(), matching, known.join(", "), seen.join(", "));
+}
+// check that a decrypted frame has an intact ending and a correct checksum
+fn frame_is_valid(msg: &[u8;8]) -> bool {
+    decode_message(msg).is_ok()
+}
+// the error of a read that didn't get `what` within its max_requests
+fn not_read(what: &str) -> CO2MonitorError {
+    CO2MonitorError::ReadFailed(format!("Unable to read the {} in the allotted number of requests", what))
+}
+// raw (co2, temperature, humidity) values as they come out of the frames
+type RawValues = (Option<u32>, Option<f32>, Option<f32>);
+// Read decrypted frames from `next_frame` until both the co2 and the temperature (and the
+// humidity if `wait_for_humidity`) were seen or `max_requests` frames were read, whichever comes
+// first. `decode` gets the values out of a frame. Invalid frames count towards `max_requests`
+// and are skipped.
+fn collect_values(max_requests: u32, wait_for_humidity: bool, decode: impl Fn(&[u8;8]) -> RawValues, mut next_frame: impl FnMut() -> Result<[u8;8], Box<dyn Error>>) -> Result<RawValues, Box<dyn Error>>{
+    let mut co2 : Option<u32> = None;
+    let mut temp : Option<f32> = None;
+    let mut humidity : Option<f32> = None;
+    let mut request_num = 0;
+    while request_num < max_requests && !(co2.is_some() && temp.is_some() && (humidity.is_some() || !wait_for_humidity)) {
+        let (co2_val, temp_val, humidity_val) = decode(&next_frame()?);
+        co2 = co2_val.or(co2);
+        temp = temp_val.or(temp);
+        humidity = humidity_val.or(humidity);
+        request_num += 1;
+    }
+    Ok((co2, temp, humidity))
+}
+// figure out if the message is about co2, temp or humidity
+fn decode_values(data: &[u8;8]) -> RawValues {
+    match decode_message(data) {
+        Ok(MessageKind::Co2(co2_val)) => (Some(co2_val), None, None),
+        Ok(MessageKind::Temperature(temp_val)) => (None, Some(temp_val), None),
+        Ok(MessageKind::Humidity(humidity_val)) => (None, None, Some(humidity_val)),
+        _ => (None, None, None),
+    }
+}
+// the mean of the readings if all their co2 values are within `tolerance_ppm` of each other
+fn stable_mean(readings: &VecDeque<CO2Reading>, tolerance_ppm: u32) -> Option<CO2Reading> {
+    let min = readings.iter().map(|r| r.co2_ppm.get()).min()?;
+    let max = readings.iter().map(|r| r.co2_ppm.get()).max()?;
+    if max - min > tolerance_ppm {
+        return None;
+    }
+    let readings : Vec<CO2Reading> = readings.iter().cloned().collect();
+    CO2Reading::average(&readings)
 }
 /// A simple struct for return values.  
 ///
@@ -93,14 +293,434 @@ fn get_magic_word() -> [u8;8]{
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct CO2Reading{
-    pub co2_ppm: u32,
-    pub temp_c: f32,
+    pub co2_ppm: CO2PPM,
+    pub temp_c: TempCelsius,
+    /// Relative humidity in percent. Only some models measure it, and it is `None` if the device
+    /// didn't send it while the co2 and temperature were read.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub humidity_pct: Option<f32>,
+}
+impl CO2Reading {
+    /// `co2_ppm` as an `f64`, for when you need to do floating point math with it
+    #[inline]
+    pub fn co2_ppm_f64(&self) -> f64 {
+        self.co2_ppm.get() as f64
+    }
+    /// The change in co2 from `baseline` to `self` in percent of `baseline`. 
+    ///
+    /// Negative if the co2 went down. Not finite if `baseline` has a co2 of 0 ppm.
+    pub fn percent_change_from(&self, baseline: &CO2Reading) -> f64 {
+        (self.co2_ppm_f64() - baseline.co2_ppm_f64()) / baseline.co2_ppm_f64() * 100.0
+    }
+    /// The reading as a single line JSON object like `{"co2_ppm":800,"temp_c":21.5}`
+    ///
+    /// This doesn't need any feature. It gives the same output as serializing with `serde_json`
+    /// when the `serde` feature is enabled.
+    pub fn to_json_string(&self) -> String {
+        match self.humidity_pct {
+            Some(humidity_pct) => format!("{{\"co2_ppm\":{},\"temp_c\":{:?},\"humidity_pct\":{:?}}}",
+                self.co2_ppm.get(), self.temp_c.get(), humidity_pct),
+            None => format!("{{\"co2_ppm\":{},\"temp_c\":{:?}}}", self.co2_ppm.get(), self.temp_c.get()),
+        }
+    }
+    /// The reading in the Graphite plaintext protocol, one `{prefix}.{name} {value} {timestamp}`
+    /// line for the co2 and one for the temperature (plus one for the humidity if there is one).
+    ///
+    /// Readings don't carry a timestamp, so `timestamp_override` is used if given and the current
+    /// unix time otherwise. The prefix may only contain ascii letters, digits, `-`, `_` and `.`.
+    pub fn to_graphite_metric(&self, prefix: &str, timestamp_override: Option<i64>) -> Result<String, CO2MonitorError> {
+        if prefix.is_empty() || !prefix.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')) {
+            return Err(CO2MonitorError::InvalidArgument(format!("`{}` is not a valid graphite prefix", prefix)));
+        }
+        let timestamp = match timestamp_override {
+            Some(timestamp) => timestamp,
+            None => SystemTime::now().duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs() as i64)
+                .unwrap_or(0),
+        };
+        let mut metric = format!("{prefix}.co2_ppm {} {timestamp}\n{prefix}.temp_c {} {timestamp}\n",
+            self.co2_ppm.get(), self.temp_c.get());
+        if let Some(humidity_pct) = self.humidity_pct {
+            metric.push_str(&format!("{prefix}.humidity_pct {} {timestamp}\n", humidity_pct));
+        }
+        Ok(metric)
+    }
+    /// A human readable line like `CO2: 1.234 ppm | Temp: 21,3°C` with the separators of your
+    /// locale. 
+    ///
+    /// The temperature (and humidity if there is one) get one decimal, written with
+    /// `decimal_sep`. The co2 is grouped by thousands with `thousands_sep` if it is given.
+    pub fn fmt_with_locale(&self, decimal_sep: char, thousands_sep: Option<char>) -> String {
+        let digits = self.co2_ppm.get().to_string();
+        let co2 = match thousands_sep {
+            Some(sep) => {
+                let mut grouped = String::new();
+                for (i, digit) in digits.chars().enumerate() {
+                    if i > 0 && (digits.len() - i) % 3 == 0 {
+                        grouped.push(sep);
+                    }
+                    grouped.push(digit);
+                }
+                grouped
+            },
+            None => digits,
+        };
+        let decimal = |value: f32| format!("{:.1}", value).replace('.', &decimal_sep.to_string());
+        let mut line = format!("CO2: {} ppm | Temp: {}°C", co2, decimal(self.temp_c.get()));
+        if let Some(humidity_pct) = self.humidity_pct {
+            line.push_str(&format!(" | Humidity: {}%", decimal(humidity_pct)));
+        }
+        line
+    }
+    /// The temperature in tenths of a kelvin, for fixed-point math. 
+    ///
+    /// Gives the same value as [convert_temperature_to_decikelvin] on the raw value the reading
+    /// was decoded from, as long as no `temp_offset_c` was set. Temperatures below 0 K give 0.
+    pub fn temp_decikelvin(&self) -> u32 {
+        // back to the sixteenths of a kelvin the device sends
+        let raw = ((self.temp_c.get() + 273.15) * 16.0).round().max(0.0);
+        convert_temperature_to_decikelvin(raw.min(f32::from(u16::MAX)) as u16)
+    }
+    /// Same line as `fmt_with_locale('.', None)` for a terminal, with the co2 colored by air
+    /// quality and the temperature in cyan. 
+    ///
+    /// The co2 is green below 800 ppm, yellow below 1200 ppm, orange below 1600 ppm and red
+    /// above that. If the `NO_COLOR` environment variable is set to anything but an empty string
+    /// the line is returned without colors, see <https://no-color.org>. Needs the `color`
+    /// feature.
+    #[cfg(feature="color")]
+    pub fn fmt_colored(&self) -> String {
+        let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+        self.fmt_colored_if(!no_color)
+    }
+    #[cfg(feature="color")]
+    fn fmt_colored_if(&self, color: bool) -> String {
+        use ansi_term::Colour;
+        if !color {
+            return self.fmt_with_locale('.', None);
+        }
+        let co2_ppm = self.co2_ppm.get();
+        let co2_color = match co2_ppm {
+            0..=799 => Colour::Green,
+            800..=1199 => Colour::Yellow,
+            // ansi has no orange, this is the closest of the 256 colors
+            1200..=1599 => Colour::Fixed(208),
+            _ => Colour::Red,
+        };
+        let mut line = format!("CO2: {} | Temp: {}",
+            co2_color.paint(format!("{} ppm", co2_ppm)),
+            Colour::Cyan.paint(format!("{:.1}°C", self.temp_c.get())));
+        if let Some(humidity_pct) = self.humidity_pct {
+            line.push_str(&format!(" | Humidity: {:.1}%", humidity_pct));
+        }
+        line
+    }
+    /// The reading as structured systemd journal fields: `CO2_PPM`, `TEMP_C` and `HUMIDITY_PCT`
+    /// (if there is one). 
+    ///
+    /// Pass them to your journal logger (for example as the extra fields of
+    /// `systemd-journal-logger`) to be able to filter with `journalctl CO2_PPM=847`. The journal
+    /// adds its own timestamp.
+    pub fn to_journald_fields(&self) -> Vec<(String, String)> {
+        let mut fields = vec![
+            ("CO2_PPM".to_owned(), self.co2_ppm.get().to_string()),
+            ("TEMP_C".to_owned(), self.temp_c.get().to_string()),
+        ];
+        if let Some(humidity_pct) = self.humidity_pct {
+            fields.push(("HUMIDITY_PCT".to_owned(), humidity_pct.to_string()));
+        }
+        fields
+    }
+    /// The reading as StatsD gauges, one `{prefix}.{name}:{value}|g` line for the co2 and one
+    /// for the temperature (plus one for the humidity if there is one), separated by newlines. 
+    ///
+    /// `prefix` is used as is, so it shouldn't contain `:`, `|` or newlines. A temperature below
+    /// zero is sent as `temp_c:0|g` followed by the negative value, because StatsD takes a signed
+    /// gauge value as a change of the gauge. See [CO2MonitorStatsdEmitter] to send them.
+    pub fn to_statsd_gauge_lines(&self, prefix: &str) -> String {
+        let temp_c = self.temp_c.get();
+        let mut lines = format!("{prefix}.co2_ppm:{}|g\n", self.co2_ppm.get());
+        if temp_c < 0.0 {
+            // reset the gauge so that the negative value sets it instead of decrementing it
+            lines.push_str(&format!("{prefix}.temp_c:0|g\n"));
+        }
+        lines.push_str(&format!("{prefix}.temp_c:{}|g", temp_c));
+        if let Some(humidity_pct) = self.humidity_pct {
+            lines.push_str(&format!("\n{prefix}.humidity_pct:{}|g", humidity_pct));
+        }
+        lines
+    }
+    /// The reading as an OpenMetrics text document, with a gauge for the co2, the temperature and
+    /// (if there is one) the humidity, each named `{metric_prefix}_{name}` and carrying `labels`. 
+    ///
+    /// The samples have no timestamp, so the scraper uses the scrape time. `metric_prefix` may
+    /// be empty, otherwise it and the label names must be valid OpenMetrics names (ascii letters,
+    /// digits and `_`, not starting with a digit). Label values are escaped.
+    pub fn to_openmetrics_string(&self, metric_prefix: &str, labels: &[(&str, &str)]) -> Result<String, CO2MonitorError> {
+        let is_valid_name = |name: &str| !name.is_empty()
+            && !name.starts_with(|c: char| c.is_ascii_digit())
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !metric_prefix.is_empty() && !is_valid_name(metric_prefix) {
+            return Err(CO2MonitorError::InvalidArgument(format!("`{}` is not a valid metric prefix", metric_prefix)));
+        }
+        let mut label_set = String::new();
+        for (name, value) in labels {
+            if !is_valid_name(name) {
+                return Err(CO2MonitorError::InvalidArgument(format!("`{}` is not a valid label name", name)));
+            }
+            let value = value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n");
+            label_set.push_str(&format!("{}{}=\"{}\"", if label_set.is_empty() { "" } else { "," }, name, value));
+        }
+        if !label_set.is_empty() {
+            label_set = format!("{{{}}}", label_set);
+        }
+        let mut metrics = vec![
+            ("co2_ppm", "CO2 concentration in parts per million", self.co2_ppm.get().to_string()),
+            ("temp_c", "Temperature in degrees celsius", format!("{:?}", self.temp_c.get())),
+        ];
+        if let Some(humidity_pct) = self.humidity_pct {
+            metrics.push(("humidity_pct", "Relative humidity in percent", format!("{:?}", humidity_pct)));
+        }
+        let mut document = String::new();
+        for (name, help, value) in metrics {
+            let name = if metric_prefix.is_empty() { name.to_owned() } else { format!("{}_{}", metric_prefix, name) };
+            document.push_str(&format!("# TYPE {name} gauge\n# HELP {name} {help}\n{name}{label_set} {value}\n"));
+        }
+        document.push_str("# EOF\n");
+        Ok(document)
+    }
+    /// The dew point in degrees celcius, or `None` if the device didn't report the humidity. 
+    ///
+    /// Uses the Magnus formula, which is accurate to about 0.1°C between -45°C and 60°C.
+    pub fn dew_point_c(&self) -> Option<f32> {
+        let humidity_pct = self.humidity_pct.filter(|h| *h > 0.0)?;
+        let temp_c = self.temp_c.get();
+        let gamma = (humidity_pct / 100.0).ln() + MAGNUS_A * temp_c / (MAGNUS_B_C + temp_c);
+        Some(MAGNUS_B_C * gamma / (MAGNUS_A - gamma))
+    }
+    /// The co2 as a mass concentration in mg/m³, assuming the air is at 25°C and 1013.25 hPa. 
+    ///
+    /// This is the usual `mg/m³ ≈ ppm * 1.8` conversion used by air quality rules. It is within a
+    /// few percent for indoor air near sea level. Use [CO2Reading::mg_per_m3_at_conditions] if you
+    /// know the real temperature and pressure.
+    pub fn mg_per_m3(&self) -> f64 {
+        self.mg_per_m3_at_conditions(25.0, 1013.25)
+    }
+    /// The co2 as a mass concentration in mg/m³ for air at `temp_c` and `pressure_hpa`. 
+    ///
+    /// Uses the ideal gas law: `mg/m³ = ppm * M * P / (R * T) / 1000` with the molar mass of co2
+    /// `M = 44.01 g/mol`, `P` in Pa, `R = 8.314 J/(mol*K)` and `T` in kelvin. This holds well for
+    /// air at everyday temperatures and pressures. `self.temp_c.get()` is a good temperature to
+    /// pass if the meter is in the room you care about.
+    pub fn mg_per_m3_at_conditions(&self, temp_c: f32, pressure_hpa: f32) -> f64 {
+        let pressure_pa = pressure_hpa as f64 * 100.0;
+        let temp_k = temp_c as f64 + 273.15;
+        self.co2_ppm_f64() * CO2_MOLAR_MASS_G_PER_MOL * pressure_pa / (GAS_CONSTANT_J_PER_MOL_K * temp_k) / 1000.0
+    }
+    /// Pack the reading into 16 bytes for storing lots of them in a file. 
+    ///
+    /// The layout is the co2 as a little endian `u64`, the temperature as a little endian `f32`
+    /// and the Unix timestamp in seconds as a little endian `u32`. Readings don't carry a time,
+    /// so the timestamp is `0xFFFFFFFF` (none), use [CO2Reading::to_bytes_with_time] to store
+    /// one. The humidity is not stored. Read it back with [CO2Reading::from_bytes].
+    pub fn to_bytes(&self) -> [u8;16] {
+        self.to_bytes_with_time(None)
+    }
+    /// Like [CO2Reading::to_bytes] but stores `time` as the timestamp. A time before 1970 or
+    /// after 2106 doesn't fit and is stored as none.
+    pub fn to_bytes_with_time(&self, time: Option<SystemTime>) -> [u8;16] {
+        let timestamp = time
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .and_then(|since_epoch| u32::try_from(since_epoch.as_secs()).ok())
+            .unwrap_or(NO_TIMESTAMP);
+        let mut bytes = [0_u8;16];
+        bytes[..8].copy_from_slice(&(self.co2_ppm.get() as u64).to_le_bytes());
+        bytes[8..12].copy_from_slice(&self.temp_c.get().to_le_bytes());
+        bytes[12..].copy_from_slice(&timestamp.to_le_bytes());
+        bytes
+    }
+    /// Unpack a reading packed with [CO2Reading::to_bytes], ignoring the timestamp. 
+    ///
+    /// Fails with [CO2MonitorError::InvalidReading] if the co2 or the temperature are out of
+    /// range.
+    pub fn from_bytes(bytes: &[u8;16]) -> Result<CO2Reading, CO2MonitorError> {
+        CO2Reading::from_bytes_with_time(bytes).map(|(reading, _)| reading)
+    }
+    /// Like [CO2Reading::from_bytes] but also returns the timestamp, `None` if there is none
+    pub fn from_bytes_with_time(bytes: &[u8;16]) -> Result<(CO2Reading, Option<SystemTime>), CO2MonitorError> {
+        let co2_ppm = u64::from_le_bytes(bytes[..8].try_into().expect("8 bytes"));
+        let co2_ppm = u32::try_from(co2_ppm)
+            .map_err(|_| CO2MonitorError::InvalidReading(format!("{} ppm is out of range", co2_ppm)))?;
+        let temp_c = f32::from_le_bytes(bytes[8..12].try_into().expect("4 bytes"));
+        let timestamp = u32::from_le_bytes(bytes[12..].try_into().expect("4 bytes"));
+        let reading = CO2Reading {
+            co2_ppm: CO2PPM::try_from(co2_ppm)?,
+            temp_c: TempCelsius::try_from(temp_c)?,
+            humidity_pct: None,
+        };
+        let time = if timestamp == NO_TIMESTAMP { None } else { Some(UNIX_EPOCH + Duration::from_secs(timestamp.into())) };
+        Ok((reading, time))
+    }
+    /// Encode the reading as a CBOR map, for binary logs where JSON is too verbose. 
+    ///
+    /// The keys are integers to keep it small: `1` for the co2, `2` for the temperature and `3`
+    /// for the humidity if there is one. `0` is kept free for a timestamp, which readings don't
+    /// have. The floats are written with the fewest bytes that keep them exact, so 812 ppm and
+    /// 21.3125°C take 9 bytes. A temperature that needs a full `f32` adds 2 more. Needs the
+    /// `cbor` feature.
+    #[cfg(feature="cbor")]
+    pub fn to_cbor_bytes(&self) -> Result<Vec<u8>, CO2MonitorError> {
+        use ciborium::Value;
+        let mut map = vec![
+            (Value::from(CBOR_KEY_CO2), Value::from(self.co2_ppm.get())),
+            (Value::from(CBOR_KEY_TEMP), Value::from(self.temp_c.get())),
+        ];
+        if let Some(humidity_pct) = self.humidity_pct {
+            map.push((Value::from(CBOR_KEY_HUMIDITY), Value::from(humidity_pct)));
+        }
+        let mut bytes = Vec::new();
+        // writing to a Vec can't fail, this only happens on a bug in ciborium
+        ciborium::ser::into_writer(&Value::Map(map), &mut bytes)
+            .map_err(|e| CO2MonitorError::InvalidReading(e.to_string()))?;
+        Ok(bytes)
+    }
+    /// Decode a reading encoded with [CO2Reading::to_cbor_bytes]. Keys it doesn't know, like a
+    /// timestamp under `0`, are ignored. 
+    ///
+    /// Fails with [CO2MonitorError::InvalidReading] if `bytes` isn't a CBOR map, the co2 or the
+    /// temperature are missing, or a value is out of range. Needs the `cbor` feature.
+    #[cfg(feature="cbor")]
+    pub fn from_cbor_bytes(bytes: &[u8]) -> Result<CO2Reading, CO2MonitorError> {
+        use ciborium::Value;
+        let invalid = |what: &str| CO2MonitorError::InvalidReading(what.to_owned());
+        let value : Value = ciborium::de::from_reader(bytes)
+            .map_err(|e| CO2MonitorError::InvalidReading(e.to_string()))?;
+        let map = value.into_map().map_err(|_| invalid("not a CBOR map"))?;
+        let (mut co2_ppm, mut temp_c, mut humidity_pct) = (None, None, None);
+        for (key, value) in map {
+            let key = key.as_integer().and_then(|key| u8::try_from(key).ok());
+            match key {
+                Some(CBOR_KEY_CO2) => co2_ppm = Some(value.as_integer()
+                    .and_then(|co2| u32::try_from(co2).ok())
+                    .ok_or_else(|| invalid("the co2 is not a u32"))?),
+                Some(CBOR_KEY_TEMP) => temp_c = Some(value.as_float()
+                    .ok_or_else(|| invalid("the temperature is not a float"))? as f32),
+                Some(CBOR_KEY_HUMIDITY) => humidity_pct = Some(value.as_float()
+                    .ok_or_else(|| invalid("the humidity is not a float"))? as f32),
+                _ => {},
+            }
+        }
+        Ok(CO2Reading {
+            co2_ppm: CO2PPM::try_from(co2_ppm.ok_or_else(|| invalid("the co2 is missing"))?)?,
+            temp_c: TempCelsius::try_from(temp_c.ok_or_else(|| invalid("the temperature is missing"))?)?,
+            humidity_pct,
+        })
+    }
+    /// The reading a fraction `t` of the way from `self` to `other`, for resampling readings to a
+    /// regular interval. 
+    ///
+    /// `t` is clamped to `0.0..=1.0` (NaN counts as 0). The humidity is only interpolated if both
+    /// readings have one.
+    pub fn interpolate(&self, other: &CO2Reading, t: f64) -> CO2Reading {
+        let t = if t.is_nan() { 0.0 } else { t.clamp(0.0, 1.0) };
+        let co2_ppm = self.co2_ppm_f64() + (other.co2_ppm_f64() - self.co2_ppm_f64()) * t;
+        let temp_c = self.temp_c.get() as f64 + (other.temp_c.get() as f64 - self.temp_c.get() as f64) * t;
+        let humidity_pct = match (self.humidity_pct, other.humidity_pct) {
+            (Some(a), Some(b)) => Some((a as f64 + (b as f64 - a as f64) * t) as f32),
+            _ => None,
+        };
+        CO2Reading {
+            // anything between two valid values is valid
+            co2_ppm: CO2PPM::try_from(co2_ppm.round() as u32).expect("interpolated co2 out of range"),
+            temp_c: TempCelsius::try_from(temp_c as f32).expect("interpolated temperature out of range"),
+            humidity_pct,
+        }
+    }
+    /// The mean of `readings`, with the co2 rounded to the nearest ppm. `None` if `readings` is
+    /// empty. 
+    ///
+    /// The humidity is averaged over the readings that have one.
+    pub fn average(readings: &[CO2Reading]) -> Option<CO2Reading> {
+        let weight = 1.0 / readings.len() as f64;
+        let weighted : Vec<(CO2Reading, f64)> = readings.iter().map(|r| (r.clone(), weight)).collect();
+        Self::weighted_average(&weighted)
+    }
+    /// Like [CO2Reading::average] but every reading counts as much as its weight. 
+    ///
+    /// The weights must not be negative and must add up to 1.0, otherwise (or if `readings` is
+    /// empty) this returns `None`.
+    pub fn weighted_average(readings: &[(CO2Reading, f64)]) -> Option<CO2Reading> {
+        if readings.is_empty() || readings.iter().any(|(_, weight)| weight.is_nan() || *weight < 0.0) {
+            return None;
+        }
+        let total_weight : f64 = readings.iter().map(|(_, weight)| weight).sum();
+        if (total_weight - 1.0).abs() > WEIGHT_SUM_TOLERANCE {
+            return None;
+        }
+        let co2_ppm : f64 = readings.iter().map(|(r, weight)| r.co2_ppm_f64() * weight).sum();
+        let temp_c : f64 = readings.iter().map(|(r, weight)| r.temp_c.get() as f64 * weight).sum();
+        // the readings without a humidity are left out, so the rest is scaled back up
+        let humidity_weight : f64 = readings.iter().filter(|(r, _)| r.humidity_pct.is_some()).map(|(_, weight)| weight).sum();
+        let humidity_sum : f64 = readings.iter().filter_map(|(r, weight)| r.humidity_pct.map(|h| h as f64 * weight)).sum();
+        Some(CO2Reading {
+            co2_ppm: CO2PPM::try_from(co2_ppm.round() as u32).ok()?,
+            temp_c: TempCelsius::try_from(temp_c as f32).ok()?,
+            humidity_pct: if humidity_weight > 0.0 { Some((humidity_sum / humidity_weight) as f32) } else { None },
+        })
+    }
+    /// A copy of the reading with the co2 scaled to what the sensor would show at sea level. 
+    ///
+    /// The sensor's reading drops with the air pressure, so readings taken at altitude are lower
+    /// than the same air would read at sea level. This uses the pressure of the International
+    /// Standard Atmosphere at `altitude_meters`. Negative altitudes are rejected.
+    pub fn normalize_to_sea_level(&self, altitude_meters: f32) -> Result<CO2Reading, CO2MonitorError> {
+        let base = 1.0 - ISA_LAPSE_PER_M * altitude_meters as f64;
+        if !altitude_meters.is_finite() || altitude_meters < 0.0 || base <= 0.0 {
+            return Err(CO2MonitorError::InvalidArgument(format!("{} m is not a valid altitude", altitude_meters)));
+        }
+        let pressure_ratio = base.powf(ISA_EXPONENT);
+        let co2_ppm = (self.co2_ppm_f64() / pressure_ratio).round() as u32;
+        Ok(CO2Reading {
+            co2_ppm: CO2PPM::try_from(co2_ppm)?,
+            ..self.clone()
+        })
+    }
+}
+/// Whatever [CO2Monitor::read_data_partial] managed to read. At least one of `co2_ppm` and
+/// `temp_c` is `Some`.
+///
+/// If you enable the `serde` feature then this also derives Serialize and Deserialize
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PartialReading{
+    pub co2_ppm: Option<CO2PPM>,
+    pub temp_c: Option<TempCelsius>,
+    pub humidity_pct: Option<f32>,
+}
+/// A reading with the humidity, see [CO2Monitor::read_full]
+///
+/// If you enable the `serde` feature then this also derives Serialize and Deserialize
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FullReading{
+    pub co2_ppm: CO2PPM,
+    pub temp_c: TempCelsius,
+    pub humidity_pct: f32,
+}
+impl From<FullReading> for CO2Reading {
+    fn from(reading: FullReading) -> Self {
+        CO2Reading {
+            co2_ppm: reading.co2_ppm,
+            temp_c: reading.temp_c,
+            humidity_pct: Some(reading.humidity_pct),
+        }
+    }
 }
 /// A simple struct to display information about the device
 ///
 /// If you enable the `serde` feature then this also derives Serialize and Deserialize
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Debug,Clone)]
+#[derive(Debug,Clone,PartialEq,Eq,Hash)]
 pub struct CO2MonitorInfo{
     pub vendor_id : u16,
     pub product_id : u16,
@@ -108,6 +728,100 @@ pub struct CO2MonitorInfo{
     pub manufacturer : String,
     pub product_name: String,
     pub serial_no: String,
+    /// The device release number from the USB descriptor. Usually the firmware revision. 
+    pub release_number: u16,
+}
+impl CO2MonitorInfo {
+    /// A udev rule that gives `group` access to this kind of device, like
+    /// `SUBSYSTEM=="usb", ATTR{idVendor}=="04d9", ATTR{idProduct}=="a052", GROUP="plugdev", MODE="0660"`
+    ///
+    /// Put it in a file like `/etc/udev/rules.d/90-co2meter.rules` and replug the device. The
+    /// group may only contain ascii letters, digits, `-` and `_`. 
+    pub fn to_udev_rule(&self, group: &str) -> Result<String, CO2MonitorError> {
+        if group.is_empty() || !group.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            return Err(CO2MonitorError::InvalidArgument(format!("`{}` is not a valid group name", group)));
+        }
+        Ok(format!("SUBSYSTEM==\"usb\", ATTR{{idVendor}}==\"{:04x}\", ATTR{{idProduct}}==\"{:04x}\", GROUP=\"{}\", MODE=\"0660\"",
+            self.vendor_id, self.product_id, group))
+    }
+    /// Whether `self` and `other` are the same physical device, comparing the vendor id, product
+    /// id and serial number. 
+    ///
+    /// Use this to recognize a monitor after it was unplugged and plugged back in, as the path
+    /// changes when it goes into another port. Note that many of these devices report the same
+    /// serial number (often the firmware version), so two identical monitors can match.
+    pub fn matches_device_info(&self, other: &CO2MonitorInfo) -> bool {
+        self.vendor_id == other.vendor_id &&
+            self.product_id == other.product_id &&
+            self.serial_no == other.serial_no
+    }
+    /// Like [CO2MonitorInfo::matches_device_info] but also requires the same path, so the device
+    /// has to be in the same USB port.
+    pub fn is_same_port(&self, other: &CO2MonitorInfo) -> bool {
+        self.matches_device_info(other) && self.path == other.path
+    }
+}
+/// [CO2MonitorInfo] with the strings borrowed from the [CO2Monitor], see
+/// [CO2Monitor::get_info_borrowed]
+///
+/// If you enable the `serde` feature then this also derives Serialize
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub struct CO2MonitorInfoRef<'a>{
+    pub vendor_id : u16,
+    pub product_id : u16,
+    pub path: &'a str,
+    pub manufacturer : &'a str,
+    pub product_name: &'a str,
+    pub serial_no: &'a str,
+    pub release_number: u16,
+}
+impl CO2MonitorInfoRef<'_> {
+    /// Copy the strings into a [CO2MonitorInfo]
+    pub fn to_owned(&self) -> CO2MonitorInfo {
+        CO2MonitorInfo {
+            vendor_id: self.vendor_id,
+            product_id: self.product_id,
+            path: self.path.to_owned(),
+            manufacturer: self.manufacturer.to_owned(),
+            product_name: self.product_name.to_owned(),
+            serial_no: self.serial_no.to_owned(),
+            release_number: self.release_number,
+        }
+    }
+}
+impl From<CO2MonitorInfoRef<'_>> for CO2MonitorInfo {
+    fn from(info: CO2MonitorInfoRef<'_>) -> Self {
+        info.to_owned()
+    }
+}
+/// [CO2MonitorInfo] plus the lower level HID details of the device
+///
+/// `usage_page` and `usage` are handy on macOS when the device can't be opened because of
+/// permissions, as they tell you which kind of input monitoring access is needed.
+///
+/// If you enable the `serde` feature then this also derives Serialize and Deserialize
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug,Clone,PartialEq,Eq,Hash)]
+pub struct CO2MonitorInfoExtended{
+    pub info : CO2MonitorInfo,
+    pub usage_page : u16,
+    pub usage : u16,
+    pub interface_number : i32,
+}
+/// Any HID device on the system, see [CO2Monitor::debug_enumerate]
+///
+/// The strings are `None` if the device doesn't report them.
+///
+/// If you enable the `serde` feature then this also derives Serialize and Deserialize
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug,Clone,PartialEq,Eq,Hash)]
+pub struct DeviceSummary{
+    pub vendor_id : u16,
+    pub product_id : u16,
+    pub path : String,
+    pub manufacturer : Option<String>,
+    pub product_name : Option<String>,
 }
 /// The main class to interact with. Instantiating this class can fail as it creates a device
 /// connection when it is created.
@@ -115,17 +829,42 @@ pub struct CO2MonitorInfo{
 ///
 pub struct CO2Monitor{
     bypass_decrypt : bool,
-    hid : HidApi,
+    // shared so that copies made with try_clone() don't need their own HidApi. Behind a mutex
+    // because re-scanning the devices needs mutable access. None for a monitor made by
+    // from_open_device() until the device has to be opened again.
+    hid : Option<Arc<Mutex<HidApi>>>,
     device_info : DeviceInfo,
-    device : Option<HidDevice>,
+    connection : ConnectionState,
     magic_table : [u8;8],
+    magic_word : [u8;8],
+    // whether hid_open() sends magic_table when reading starts
+    send_magic_table : bool,
+    report_id_prefix : bool,
+    frame_layout : FrameLayout,
+    warmup_frames : u32,
+    temp_offset_c : f32,
+    co2_offset_ppm : i32,
+    read_interval : Option<Duration>,
+    decoder : Option<CustomDecoder>,
+    // counted while a read is in progress, copied to last_read_diagnostics when it is done
+    diagnostics : ReadDiagnostics,
+    last_read_diagnostics : Option<ReadDiagnostics>,
+    // when read_data_inner() last returned a reading
+    last_reading_at : Option<Instant>,
+}
+// Whether a CO2Monitor currently holds an open connection to its device
+enum ConnectionState {
+    Closed,
+    Open(HidDevice),
 }
 impl CO2Monitor {
     /// This is the default way to create a CO2Monitor that you will most certainly use. 
     /// It does not bypass decryption and it assumes that grabs the first co2 monitor it sees. Do
     /// not use this if you have multiple co2 monitors on your computer. 
     ///
-    /// Equivalent to CO2Monitor::new(false, None). 
+    /// Equivalent to CO2Monitor::new(false, None). If no device is found, the HID devices that
+    /// were seen are logged with `log::warn!`, which is the first thing to look at when your
+    /// monitor isn't detected.
     /// 
     pub fn default() -> Result<CO2Monitor, Box<dyn Error>> {
         Self::new(false, None)
@@ -133,156 +872,1148 @@ impl CO2Monitor {
     /// Use this if you know you need to bypass decryption (try to do this if the package is not
     /// working. Apparently some models don't have the encryption) or if you need to specify one of
     /// the multiple CO2 monitors you have on your system.  
+    ///
+    /// Use [CO2MonitorBuilder] if you need more settings than this. 
     pub fn new(bypass_decrypt: bool, interface_path: Option<String>) -> Result<CO2Monitor, Box<dyn Error>> {
         let hid = HidApi::new()?;
-        let device_info = Self::find_device(&hid, interface_path).ok_or("Unable to find the hid device")?;
-
-
-        Ok(CO2Monitor {
+        let device_info = Self::find_device(hid.device_list(), interface_path.as_deref())
+            .cloned()
+            .inspect_err(|_| log_enumerated_devices(&hid))?;
+        Ok(Self::from_device_info(Arc::new(Mutex::new(hid)), device_info, bypass_decrypt))
+    }
+    /// Use the co2 monitor with the given serial number. 
+    ///
+    /// Unlike the interface path used by [CO2Monitor::new], the serial number is the same on every
+    /// OS, so it is a better fit for config files that move between machines.
+    pub fn find_by_serial(serial: &str, bypass_decrypt: bool) -> Result<CO2Monitor, Box<dyn Error>> {
+        let hid = HidApi::new()?;
+        let device_info = Self::find_device_matching(&hid, |device| device.serial_number() == Some(serial))
+            .ok_or_else(|| format!("Unable to find a co2 monitor with serial number {}", serial))?;
+        Ok(Self::from_device_info(Arc::new(Mutex::new(hid)), device_info, bypass_decrypt))
+    }
+    // the first co2 monitor whose path matches `pattern`, see CO2MonitorBuilder::path_pattern
+    #[cfg(feature="regex")]
+    fn find_by_path_pattern(pattern: &regex::Regex, bypass_decrypt: bool) -> Result<CO2Monitor, Box<dyn Error>> {
+        let hid = HidApi::new()?;
+        let device_info = match Self::find_device_matching(&hid, |device| device.path().to_str().map(|path| pattern.is_match(path)).unwrap_or(false)) {
+            Some(device_info) => device_info,
+            None => {
+                let paths : Vec<String> = hid.device_list()
+                    .filter(|device| is_co2_monitor(device))
+                    .map(|device| device.path().to_string_lossy().into_owned())
+                    .collect();
+                return Err(format!("No co2 monitor path matches {}. Available paths: [{}]", pattern, paths.join(", ")).into());
+            },
+        };
+        Ok(Self::from_device_info(Arc::new(Mutex::new(hid)), device_info, bypass_decrypt))
+    }
+    /// Use the first co2 monitor whose manufacturer string contains `name`, ignoring case. 
+    ///
+    /// If several match, the first one in enumeration order is used and a warning is logged.
+    pub fn find_by_manufacturer(name: &str, bypass_decrypt: bool) -> Result<CO2Monitor, Box<dyn Error>> {
+        let hid = HidApi::new()?;
+        let name_lower = name.to_lowercase();
+        let matching : Vec<&DeviceInfo> = hid.device_list()
+            .filter(|device| is_co2_monitor(device))
+            .filter(|device| device.manufacturer_string()
+                .map(|manufacturer| manufacturer.to_lowercase().contains(&name_lower))
+                .unwrap_or(false))
+            .collect();
+        if matching.len() > 1 {
+            warn!("{} co2 monitors have a manufacturer matching {}, using the first one", matching.len(), name);
+        }
+        let device_info = matching.first()
+            .map(|device| (*device).clone())
+            .ok_or_else(|| format!("Unable to find a co2 monitor with a manufacturer matching {}", name))?;
+        Ok(Self::from_device_info(Arc::new(Mutex::new(hid)), device_info, bypass_decrypt))
+    }
+    /// List every HID device on the system, not just the co2 monitors. 
+    ///
+    /// Paste this into your issue if your monitor isn't detected, so that its ids can be added.
+    pub fn debug_enumerate() -> Result<Vec<DeviceSummary>, Box<dyn Error>> {
+        let hid = HidApi::new()?;
+        Ok(hid.device_list()
+            .map(|device| DeviceSummary {
+                vendor_id: device.vendor_id(),
+                product_id: device.product_id(),
+                path: device.path().to_string_lossy().into_owned(),
+                manufacturer: device.manufacturer_string().map(str::to_owned),
+                product_name: device.product_string().map(str::to_owned),
+            })
+            .collect())
+    }
+    /// Return a [CO2Monitor] for every co2 monitor plugged in. 
+    ///
+    /// The monitors share one HID context and start out closed, so the only thing that can fail
+    /// is enumerating the HID devices. If no co2 monitor is plugged in you get an empty `Vec`.
+    /// Use [CO2Monitor::find_all_results] to also find out which of them can't be opened.
+    pub fn find_all(bypass_decrypt: bool) -> Result<Vec<CO2Monitor>, CO2MonitorError> {
+        let hid = HidApi::new()?;
+        let device_infos : Vec<DeviceInfo> = hid.device_list()
+            .filter(|device| is_co2_monitor(device))
+            .cloned()
+            .collect();
+        let hid = Arc::new(Mutex::new(hid));
+        Ok(device_infos.into_iter()
+            .map(|device_info| Self::from_device_info(Arc::clone(&hid), device_info, bypass_decrypt))
+            .collect())
+    }
+    /// Like [CO2Monitor::find_all] but opens every monitor once (and closes it again), giving the
+    /// error for those that fail instead of the monitor. 
+    ///
+    /// One monitor you don't have the permissions for doesn't keep you from using the others.
+    pub fn find_all_results(bypass_decrypt: bool) -> Result<Vec<Result<CO2Monitor, CO2MonitorError>>, CO2MonitorError> {
+        Ok(Self::find_all(bypass_decrypt)?.into_iter()
+            .map(|mut co2| {
+                co2.hid_open(co2.send_magic_table, false)
+                    .and_then(|_| co2.hid_close())
+                    .map_err(CO2MonitorError::from_boxed)?;
+                Ok(co2)
+            })
+            .collect())
+    }
+    // build a closed CO2Monitor for an already discovered device
+    fn from_device_info(hid: Arc<Mutex<HidApi>>, device_info: DeviceInfo, bypass_decrypt: bool) -> CO2Monitor {
+        Self::with_connection(Some(hid), device_info, ConnectionState::Closed, bypass_decrypt)
+    }
+    // a CO2Monitor with the default settings
+    fn with_connection(hid: Option<Arc<Mutex<HidApi>>>, device_info: DeviceInfo, connection: ConnectionState, bypass_decrypt: bool) -> CO2Monitor {
+        CO2Monitor {
             bypass_decrypt,
             hid,
             device_info,
-            device:None,
+            connection,
             magic_table : [0_u8;8],
+            magic_word : *CO2MON_MAGIC_WORD,
+            send_magic_table : true,
+            report_id_prefix : false,
+            frame_layout : FrameLayout::default(),
+            warmup_frames : 0,
+            temp_offset_c : 0.0,
+            co2_offset_ppm : 0,
+            read_interval : None,
+            decoder : None,
+            diagnostics : ReadDiagnostics::default(),
+            last_read_diagnostics : None,
+            last_reading_at : None,
+        }
+    }
+    /// Wrap a device you opened yourself, for when you manage the HID devices at a lower level. 
+    ///
+    /// The magic table is not sent, so the device has to be set up already (see
+    /// [CO2MonitorBuilder::magic_table]). Use [CO2Monitor::read_data_open] to read from it
+    /// without closing it. The other reads close the device when they are done and open it
+    /// again next time, which needs a new `HidApi` and so fails while you still hold yours.
+    pub fn from_open_device(device: HidDevice, device_info: DeviceInfo, bypass_decrypt: bool) -> CO2Monitor {
+        Self::with_connection(None, device_info, ConnectionState::Open(device), bypass_decrypt)
+    }
+    /// Like [CO2Monitor::default] but figures out `bypass_decrypt` on its own. 
+    ///
+    /// This opens the first co2 monitor it sees, reads a few frames and checks whether they decode
+    /// correctly with or without decryption. Use this if you are not sure which kind of device you
+    /// have.
+    pub fn auto() -> Result<CO2Monitor, Box<dyn Error>> {
+        let mut co2 = Self::new(false, None)?;
+        co2.bypass_decrypt = co2.probe_bypass_decrypt(PROBE_FRAMES)?;
+        Ok(co2)
+    }
+    /// Create another [CO2Monitor] for the same physical device. 
+    ///
+    /// The copy shares the HID context and settings of `self` but does not share its connection:
+    /// it starts out closed and opens its own connection when you read from it. This lets you hand
+    /// a monitor to each thread that needs one. `self` is not affected.
+    pub fn try_clone(&self) -> Result<CO2Monitor, Box<dyn Error>> {
+        Ok(CO2Monitor {
+            bypass_decrypt: self.bypass_decrypt,
+            hid: self.hid.clone(),
+            device_info: self.device_info.clone(),
+            connection: ConnectionState::Closed,
+            magic_table: self.magic_table,
+            magic_word: self.magic_word,
+            send_magic_table: self.send_magic_table,
+            report_id_prefix: self.report_id_prefix,
+            frame_layout: self.frame_layout,
+            warmup_frames: self.warmup_frames,
+            temp_offset_c: self.temp_offset_c,
+            co2_offset_ppm: self.co2_offset_ppm,
+            read_interval: self.read_interval,
+            decoder: self.decoder.clone(),
+            diagnostics: ReadDiagnostics::default(),
+            last_read_diagnostics: None,
+            last_reading_at: None,
         })
     }
     /// Return a [CO2MonitorInfo] about the device
     pub fn info(&self) -> CO2MonitorInfo {
-        CO2MonitorInfo { 
+        self.get_info_borrowed().to_owned()
+    }
+    /// Like [CO2Monitor::info] but borrows the strings instead of allocating them
+    pub fn get_info_borrowed(&self) -> CO2MonitorInfoRef<'_> {
+        CO2MonitorInfoRef { 
             vendor_id: self.device_info.vendor_id(),
             product_id: self.device_info.product_id(),
-            path: String::from(self.device_info.path().to_str().unwrap_or("Error")),
-            manufacturer: String::from(self.device_info.manufacturer_string().unwrap_or("None provided")),
-            product_name: String::from(self.device_info.product_string().unwrap_or("None provided")),
-            serial_no: String::from(self.device_info.serial_number().unwrap_or("None provided")) 
+            path: self.device_info.path().to_str().unwrap_or("Error"),
+            manufacturer: self.device_info.manufacturer_string().unwrap_or("None provided"),
+            product_name: self.device_info.product_string().unwrap_or("None provided"),
+            serial_no: self.device_info.serial_number().unwrap_or("None provided"),
+            release_number: self.device_info.release_number(),
+        }
+    }
+    /// Return a [CO2MonitorInfoExtended] about the device
+    pub fn get_info_extended(&self) -> CO2MonitorInfoExtended {
+        CO2MonitorInfoExtended {
+            info: self.info(),
+            usage_page: self.device_info.usage_page(),
+            usage: self.device_info.usage(),
+            interface_number: self.device_info.interface_number(),
         }
     }
+    /// Re-scan the HID bus and check if this device is still plugged in. 
+    ///
+    /// The device is matched by its path and serial number and is never opened, so this is cheap
+    /// enough to call before every read in a long running program. Returns `false` if the HID
+    /// devices can't be enumerated.
+    ///
+    /// A monitor made with [CO2Monitor::from_open_device] has no HID context of its own, and a
+    /// new one can't be created while yours is alive, so this would always return `false` for it.
+    /// Use [CO2Monitor::is_device_present_in] with your `HidApi` instead.
+    pub fn is_device_present(&self) -> bool {
+        let result = match &self.hid {
+            Some(hid) => match hid.lock() {
+                Ok(mut hid) => self.is_device_present_in(&mut hid),
+                Err(_) => return false,
+            },
+            None => HidApi::new().map_err(CO2MonitorError::from).and_then(|mut hid| self.is_device_present_in(&mut hid)),
+        };
+        result.unwrap_or(false)
+    }
+    /// Like [CO2Monitor::is_device_present] but re-scans with the given HID context, and fails
+    /// instead of returning `false` if the devices can't be enumerated
+    pub fn is_device_present_in(&self, hid: &mut HidApi) -> Result<bool, CO2MonitorError> {
+        hid.refresh_devices()?;
+        Ok(self.is_listed(hid))
+    }
+    // whether `hid` lists this device
+    fn is_listed(&self, hid: &HidApi) -> bool {
+        hid.device_list().any(|device| {
+            let (ids, own) = (DeviceIds::from(device), DeviceIds::from(&self.device_info));
+            ids.path == own.path && ids.serial == own.serial
+        })
+    }
     // find the correct co2 monitor. Used in CO2Monitor::new(..)
-    fn find_device(hid: &HidApi, interface_path: Option<String>) -> Option<DeviceInfo>{
+    // Fails with PathNotFound if there are co2 monitors but none at `interface_path`, so that
+    // a wrong path can be told apart from a missing device
+    // Takes the devices instead of the HidApi so that it doesn't depend on what is plugged in.
+    pub(crate) fn find_device<'a, D>(devices: impl Iterator<Item = &'a D>, interface_path: Option<&str>) -> Result<&'a D, CO2MonitorError>
+    where &'a D: Into<DeviceIds<'a>> {
+        let mut monitors = devices.filter(|&device| device.into().is_co2_monitor());
+        let path = match interface_path {
+            None => return monitors.next().ok_or(CO2MonitorError::DeviceNotFound),
+            Some(path) => path,
+        };
+        let mut any_monitor = false;
+        for device in monitors {
+            if device.into().path.to_str() == Ok(path) {
+                return Ok(device);
+            }
+            any_monitor = true;
+        }
+        if any_monitor {
+            Err(CO2MonitorError::PathNotFound(path.to_owned()))
+        } else {
+            Err(CO2MonitorError::DeviceNotFound)
+        }
+    }
+    // find the first co2 monitor for which `matches` returns true
+    fn find_device_matching(hid: &HidApi, matches: impl Fn(&DeviceInfo) -> bool) -> Option<DeviceInfo>{
         for device in hid.device_list(){
             //println!("{:04x}:{:04x}", device.vendor_id(), device.product_id());
-            if device.vendor_id() == CO2MON_HID_VENDOR_ID &&
-                device.product_id() == CO2MON_HID_PRODUCT_ID {
-                // If we are supplied a path, ensure that we skip unmatched ones
-                if interface_path.is_some() &&
-                    (device.path().to_str().unwrap() != interface_path.as_ref().unwrap().as_str()){
-                        continue;
-                }
+            if is_co2_monitor(device) && matches(device) {
                 return Some(device.clone());
             }
         }
         None
-    } 
-    // open the connection to the device. Assumes that there is no open connection. 
-    fn hid_open(&mut self, send_magic_tables : bool) -> Result<(), Box<dyn Error>>{
-        assert!(self.device.is_none());
-        self.device = Some(self.device_info.open_device(&self.hid)?);
+    }
+    // open the connection to the device. If it is already open it is reused, unless force_reopen
+    // is set in which case it is closed and opened again.
+    fn hid_open(&mut self, send_magic_tables : bool, force_reopen : bool) -> Result<(), Box<dyn Error>>{
+        if let ConnectionState::Open(_) = self.connection {
+            if !force_reopen {
+                return Ok(());
+            }
+            self.connection = ConnectionState::Closed;
+        }
+        let start = Instant::now();
+        let context = self.hid_context()?;
+        let hid = context.lock().map_err(|_| "The HID context lock is poisoned")?;
+        let device = self.device_info.open_device(&hid)?;
         if send_magic_tables{
-            self.device.as_ref().ok_or("No device found to send tables to. Strange.")?.send_feature_report(&self.magic_table)?;
+            // the device is dropped (and closed) if this fails
+            device.send_feature_report(&self.magic_table)?;
         }
+        self.connection = ConnectionState::Open(device);
+        self.diagnostics.open_duration += start.elapsed();
         Ok(())
     }
-    // close the connection to the device. Assumes that a connection is already open.
+    // the shared HID context, created here for a monitor made by from_open_device()
+    fn hid_context(&mut self) -> Result<Arc<Mutex<HidApi>>, Box<dyn Error>>{
+        if let Some(hid) = &self.hid {
+            return Ok(Arc::clone(hid));
+        }
+        let hid = Arc::new(Mutex::new(HidApi::new()?));
+        self.hid = Some(Arc::clone(&hid));
+        Ok(hid)
+    }
+    // open the device and throw away the warmup frames, unless it is already open. The device
+    // is closed again on failure
+    fn hid_open_for_reading(&mut self) -> Result<(), Box<dyn Error>>{
+        if let ConnectionState::Open(_) = self.connection {
+            // frames may have queued up while the open device sat idle
+            if self.warmup_frames > 0 {
+                self.flush()?;
+            }
+            return Ok(());
+        }
+        self.hid_open(self.send_magic_table, false)?;
+        // throw away frames that may be stale from before the device was opened
+        for _ in 0..self.warmup_frames {
+            if let Err(e) = self.hid_read() {
+                self.connection = ConnectionState::Closed;
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+    // close the connection to the device. Fails with NotOpen if there is no open connection.
     fn hid_close(&mut self) -> Result<(), Box<dyn Error>>{
-        assert!(self.device.is_some());
-        self.device = None; // This should call the destructor and close it
+        if let ConnectionState::Closed = self.connection {
+            return Err(CO2MonitorError::NotOpen.into());
+        }
+        self.connection = ConnectionState::Closed; // This should call the destructor and close it
         Ok(())
     }
-    // Read raw data from the device
+    // the open device, or NotOpen
+    fn device(&self) -> Result<&HidDevice, CO2MonitorError>{
+        match &self.connection {
+            ConnectionState::Open(device) => Ok(device),
+            ConnectionState::Closed => Err(CO2MonitorError::NotOpen),
+        }
+    }
+    // Read data from the device, decrypting it unless bypass_decrypt is set
     fn hid_read(&mut self) -> Result<[u8;8], Box<dyn Error>>{
+        let data = self.hid_read_raw()?;
+        if self.bypass_decrypt{
+            return Ok(self.frame_layout.normalize(data));
+        }
+        Ok(self.frame_layout.normalize(self.decrypt(data)))
+    }
+    // Read raw data from the device
+    fn hid_read_raw(&mut self) -> Result<[u8;8], Box<dyn Error>>{
+        self.diagnostics.read_count += 1;
         let mut data : [u8;8] = [0;8];
-        self.device.as_ref().ok_or("Device is not opened. Call hid_open before hid_read()")?.read(&mut data)?;
-        Ok(self.decrypt(data))
+        if self.report_id_prefix {
+            // drop the leading report id
+            let mut report : [u8;9] = [0;9];
+            let len = self.device()?.read(&mut report)?;
+            if len < report.len() {
+                return Err(CO2MonitorError::ShortRead(len.saturating_sub(1)).into());
+            }
+            data.copy_from_slice(&report[1..]);
+        } else {
+            let len = self.device()?.read(&mut data)?;
+            // the rest of the buffer would be zeros that decode to garbage
+            if len < data.len() {
+                return Err(CO2MonitorError::ShortRead(len).into());
+            }
+        }
+        Ok(data)
     }
     // decrypt the message (used inside hid_read(..))
     fn decrypt(&self, data : [u8;8]) -> [u8;8] {
-        if self.bypass_decrypt{
-            return data;
-        }
-        // rearrange data and turn into u64
-        let rearranged_data : [u8;8] = [
-            data[2],
-            data[4],
-            data[0],
-            data[7],
-            data[1],
-            data[6],
-            data[5],
-            data[3]
-        ];
-        let message = list_to_u64(&rearranged_data);
-        // XOR with magic table
-        let mut result = message ^ list_to_u64(&self.magic_table);
-        // cyclic shift by 3 to the right
-        result = (result >> 3) | (result << 61);
-        let result_list = u64_to_list(result);
-        // They really should enable the array_zip feature... Really stupid that they haven't
-        let magic_word = get_magic_word();
-        let mut i = 0;
-        result_list.map(|r| r.wrapping_sub(magic_word[{i+=1;i-1}]))
-
+        decrypt_frame(data, &self.magic_table, &self.magic_word)
     }
-    // figure out if the message is about co2 or temp
-    fn decode_message(&self, msg : [u8;8]) -> (Option<u32>,Option<f32>){
-        // verify end of the message is intact
-        if msg[5]!=0 || msg[6]!=0 || msg[7] !=0 || msg[4]!= CODE_END_MESSAGE{
-            return (None, None);
-        }
-        // verify checksum
-        if (msg[0].wrapping_add(msg[1]).wrapping_add(msg[2])) != msg[3]{
-            return (None,None);
-        }
-        let value : u16 = ((msg[1] as u16) << 8) | msg[2] as u16;
-        match msg[0] {
-            CODE_CO2 => (Some(value as u32), None),
-            CODE_TEMPERATURE => (None, Some(convert_temperature_to_celcius(value))),
-            _ =>(None,None),
-        }
-    }
-    fn read_data_inner(&mut self, max_requests: u32) -> Result<CO2Reading, Box<dyn Error>>{
-        let mut co2 : Option<u32> = None;
-        let mut temp : Option<f32> = None;
-        let mut request_num = 0;
-        // XOR, keep going until both the co2 and temp are Some(..)
-        while (request_num < max_requests) ^ (co2.is_some() && temp.is_some()) {
-            let data = self.hid_read()?;
-            let message = self.decode_message(data);
-            match message {
-                (co2_val,None) => {co2 = co2_val},
-                (None, temp_val) => {temp = temp_val},
-                _ => {},
-            }
-            request_num += 1;
+    /// Decode a frame as it was read from the device. 
+    ///
+    /// The frame is decrypted first unless `bypass_decrypt` is set. Unlike [CO2Monitor::read_data]
+    /// this gives you every kind of frame, including the ones this crate doesn't interpret.
+    pub fn decode_frame(&self, frame: [u8;8]) -> Result<MessageKind, DecodeError> {
+        let msg = if self.bypass_decrypt { frame } else { self.decrypt(frame) };
+        self.frame_layout.decode(&msg)
+    }
+    // read a full reading from the open device. Fails with Cancelled as soon as `cancel` is set
+    fn read_data_inner(&mut self, max_requests: u32, cancel: Option<&AtomicBool>) -> Result<CO2Reading, Box<dyn Error>>{
+        let partial = self.read_partial_inner(max_requests, cancel)?;
+        let reading = CO2Reading {
+            co2_ppm: partial.co2_ppm.ok_or_else(|| not_read("co2"))?,
+            temp_c : partial.temp_c.ok_or_else(|| not_read("temperature"))?,
+            humidity_pct : partial.humidity_pct,
+        };
+        self.last_reading_at = Some(Instant::now());
+        Ok(reading)
+    }
+    // apply the offsets set with the builder. The co2 saturates at 0 instead of going negative
+    fn calibrated_co2(&self, co2_val: u32) -> Result<CO2PPM, CO2MonitorError> {
+        CO2PPM::try_from(co2_val.saturating_add_signed(self.co2_offset_ppm))
+    }
+    fn calibrated_temp(&self, temp_val: f32) -> Result<TempCelsius, CO2MonitorError> {
+        TempCelsius::try_from(temp_val + self.temp_offset_c)
+    }
+    // Read the next frame of a reading, sleeping `read_interval` first unless `first` is set
+    // (which is cleared). Fails with Cancelled as soon as `cancel` is set, also while sleeping
+    fn read_frame(&mut self, first: &mut bool, cancel: Option<&AtomicBool>) -> Result<[u8;8], Box<dyn Error>>{
+        let cancelled = || cancel.is_some_and(|cancel| cancel.load(Ordering::SeqCst));
+        match self.read_interval {
+            Some(interval) if !*first => {
+                // in slices, so that a cancelled read doesn't wait out the whole interval
+                let start = Instant::now();
+                while let Some(left) = interval.checked_sub(start.elapsed()).filter(|left| !left.is_zero()) {
+                    if cancelled() {
+                        return Err(CO2MonitorError::Cancelled.into());
+                    }
+                    thread::sleep(left.min(CANCEL_POLL_INTERVAL));
+                }
+            },
+            _ => *first = false,
         }
-        Ok(CO2Reading {
-            co2_ppm: co2.ok_or("Unable to read the co2 in the allotted number of requests")?,
-            temp_c : temp.ok_or("Unable to read the temperature in the allotted number of requests")?,
+        if cancelled() {
+            return Err(CO2MonitorError::Cancelled.into());
+        }
+        self.hid_read()
+    }
+    // how the reads get the values out of a frame: with the decoder set with the builder if there
+    // is one, otherwise like decode_values
+    fn value_decoder(&self) -> impl Fn(&[u8;8]) -> RawValues {
+        let decoder = self.decoder.clone();
+        move |data: &[u8;8]| match &decoder {
+            Some(decoder) => {
+                let (co2_val, temp_val) = decoder.decode(*data);
+                (co2_val, temp_val, None)
+            },
+            None => decode_values(data),
+        }
+    }
+    fn read_partial_inner(&mut self, max_requests: u32, cancel: Option<&AtomicBool>) -> Result<PartialReading, Box<dyn Error>>{
+        let decode = self.value_decoder();
+        let mut first = true;
+        let (co2, temp, humidity) = collect_values(max_requests, false, decode, || self.read_frame(&mut first, cancel))?;
+        Ok(PartialReading {
+            co2_ppm: co2.map(|co2_val| self.calibrated_co2(co2_val)).transpose()?,
+            temp_c : temp.map(|temp_val| self.calibrated_temp(temp_val)).transpose()?,
+            humidity_pct : humidity,
         })
 
     }
+    // Read `frames` raw frames and check whether they decode better without decryption.
+    // Returns the value that bypass_decrypt should have.
+    fn probe_bypass_decrypt(&mut self, frames: u32) -> Result<bool, Box<dyn Error>>{
+        // start from a freshly initialized device so that the frames are comparable
+        self.hid_open(self.send_magic_table, true)?;
+        let result = self.probe_bypass_decrypt_inner(frames);
+        self.hid_close()?;
+        result
+    }
+    fn probe_bypass_decrypt_inner(&mut self, frames: u32) -> Result<bool, Box<dyn Error>>{
+        let mut valid_encrypted = 0;
+        let mut valid_plain = 0;
+        for _ in 0..frames {
+            let data = self.hid_read_raw()?;
+            if frame_is_valid(&self.frame_layout.normalize(self.decrypt(data))) {
+                valid_encrypted += 1;
+            }
+            if frame_is_valid(&self.frame_layout.normalize(data)) {
+                valid_plain += 1;
+            }
+        }
+        if valid_encrypted == 0 && valid_plain == 0 {
+            return Err("Unable to decode any frames with or without decryption".into());
+        }
+        Ok(valid_plain > valid_encrypted)
+    }
+    /// Read `sample_count` frames and report how many of them were valid, which codes showed up
+    /// and the range of the values. 
+    ///
+    /// Use this when the device starts giving odd readings to see whether it is sending garbage.
+    /// Whether the frames are decrypted is decided from the frames themselves, ignoring
+    /// `bypass_decrypt`.
+    pub fn health_check(&mut self, sample_count: u32) -> Result<CO2MonitorHealthReport, Box<dyn Error>>{
+        let session = self.open_for_reading()?;
+        let mut raw_frames = Vec::with_capacity(sample_count as usize);
+        for _ in 0..sample_count {
+            raw_frames.push(session.monitor.hid_read_raw()?);
+        }
+        drop(session);
+        let decrypted : Vec<[u8;8]> = raw_frames.iter().map(|frame| self.frame_layout.normalize(self.decrypt(*frame))).collect();
+        let plain : Vec<[u8;8]> = raw_frames.iter().map(|frame| self.frame_layout.normalize(*frame)).collect();
+        let valid_decrypted = decrypted.iter().filter(|frame| frame_is_valid(frame)).count();
+        let valid_plain = plain.iter().filter(|frame| frame_is_valid(frame)).count();
+        let encryption_detected = valid_decrypted > valid_plain;
+        let frames = if encryption_detected { &decrypted } else { &plain };
+        Ok(CO2MonitorHealthReport::from_frames(frames, encryption_detected))
+    }
+    /// Send a raw feature report to the device. 
+    ///
+    /// This is an escape hatch for vendor specific commands (like calibration) that this crate
+    /// does not know about. There is no public documentation of the command set of these devices,
+    /// so sending the wrong report can leave your monitor misconfigured. Use at your own risk.
+    /// The device is only closed afterwards if it wasn't open before.
+    pub fn send_raw_feature_report(&mut self, report: &[u8]) -> Result<(), Box<dyn Error>>{
+        // leave a connection that was already open as it is
+        let was_open = matches!(self.connection, ConnectionState::Open(_));
+        self.hid_open(false, false)?;
+        let result = match self.device() {
+            Ok(device) => device.send_feature_report(report).map_err(|e| e.into()),
+            Err(e) => Err(e.into()),
+        };
+        if !was_open {
+            self.hid_close()?;
+        }
+        result
+    }
     /// Returns a [CO2Reading] if successful. 
     /// 
     /// `max_requests` specifies the number of times to poll the device. A reccomeneded value is
-    /// `50`
+    /// [DEFAULT_MAX_REQUESTS]. Reading stops as soon as both the co2 and the temperature were seen, so usually far
+    /// fewer frames are read. It fails if one of them is still missing after `max_requests`.
     ///
+    /// How long the device took to open and how many frames were read are kept, see
+    /// [CO2Monitor::last_read_diagnostics].
     pub fn read_data(&mut self, max_requests: u32) -> Result<CO2Reading, Box<dyn Error>>{
-        self.hid_open(true)?;
-        let result = self.read_data_inner( max_requests);
-        self.hid_close()?;
+        let start = Instant::now();
+        self.diagnostics = ReadDiagnostics::default();
+        let result = self.open_for_reading().and_then(|mut session| session.read_data(max_requests));
+        self.diagnostics.total_duration = start.elapsed();
+        self.last_read_diagnostics = Some(self.diagnostics);
+        result
+    }
+    /// Take one reading and make sure the device is closed afterwards, also when the reading
+    /// failed or the monitor was made with [CO2Monitor::from_open_device]. 
+    ///
+    /// Meant for battery powered loggers that read every few minutes: with no handle left the
+    /// OS can suspend the USB port in between. Afterwards the HID bus is re-scanned to check that
+    /// the device is still listed, and a warning is logged with `log::warn!` if it isn't.
+    pub fn read_lowpower(&mut self, max_requests: u32) -> Result<CO2Reading, CO2MonitorError>{
+        let result = self.read_data(max_requests).map_err(CO2MonitorError::from);
+        // read_data closes it when the session ends, this also covers the paths that never get
+        // a session
+        self.connection = ConnectionState::Closed;
+        if !self.is_device_present() {
+            warn!("The co2 monitor at {} is no longer listed after closing it", self.device_info.path().to_string_lossy());
+        }
         result
     }
+    /// Take a reading, write it to `writer` as one line in `format` and return it. 
+    ///
+    /// Works with anything that implements [Write], like `stdout()`, a `File` or a `TcpStream`.
+    pub fn read_and_log(&mut self, writer: &mut impl Write, format: OutputFormat, max_requests: u32) -> Result<CO2Reading, CO2MonitorError>{
+        let reading = self.read_data(max_requests)?;
+        writeln!(writer, "{}", format.format(&reading))?;
+        Ok(reading)
+    }
+    /// [CO2Monitor::read_data] with [DEFAULT_MAX_REQUESTS]
+    pub fn read_data_default(&mut self) -> Result<CO2Reading, Box<dyn Error>>{
+        self.read_data(DEFAULT_MAX_REQUESTS)
+    }
+    /// Timings of the last call to [CO2Monitor::read_data], whether it succeeded or not. `None`
+    /// if it wasn't called yet.
+    pub fn last_read_diagnostics(&self) -> Option<ReadDiagnostics> {
+        self.last_read_diagnostics
+    }
+    /// How long ago the last successful read returned, to tell how stale your data is. `None` if
+    /// there was none yet.
+    ///
+    /// Every read that returns a value counts, including the ones through a [CO2MonitorSession],
+    /// but not [CO2Monitor::health_check].
+    pub fn last_reading_age(&self) -> Option<Duration> {
+        self.last_reading_at.map(|at| at.elapsed())
+    }
+    /// Turn the monitor into an endless iterator of readings. 
+    ///
+    /// The device is kept open between readings. If a read fails the error is yielded and the
+    /// device is reopened on the next call, so you can keep going after it was unplugged.
+    pub fn readings(self, max_requests: u32) -> CO2MonitorReadings {
+        CO2MonitorReadings::new(self, max_requests)
+    }
+    /// [CO2Monitor::readings] as a trait object, so that it can be stored next to other sources
+    /// of readings (like a replay of a log file) behind one type.
+    pub fn boxed_readings(self, max_requests: u32) -> Box<dyn Iterator<Item = Result<CO2Reading, Box<dyn Error>>> + Send> {
+        Box::new(self.readings(max_requests))
+    }
+    /// Read the monitor on a new thread every `interval` and send the results to the returned
+    /// channel. 
+    ///
+    /// The thread stops when the [Receiver] is dropped. `max_requests` is
+    /// [DEFAULT_MAX_REQUESTS].
+    pub fn spawn_into_channel(self, interval: Duration) -> (JoinHandle<()>, Receiver<Result<CO2Reading, CO2MonitorError>>) {
+        let (sender, receiver) = mpsc::channel();
+        let handle = thread::spawn(move || {
+            for result in self.readings(DEFAULT_MAX_REQUESTS) {
+                if sender.send(result.map_err(CO2MonitorError::from)).is_err() {
+                    break;
+                }
+                thread::sleep(interval);
+            }
+        });
+        (handle, receiver)
+    }
+    /// Like [CO2Monitor::read_data] but stops with [CO2MonitorError::Cancelled] once `cancel` is
+    /// set. 
+    ///
+    /// The flag is checked before every frame and every 100 ms while waiting out the
+    /// [read_interval](CO2MonitorBuilder::read_interval), so this returns within one frame (about
+    /// a second) of it being set. Handy for shutting down cleanly on SIGTERM.
+    pub fn read_data_cancellable(&mut self, max_requests: u32, cancel: &AtomicBool) -> Result<CO2Reading, CO2MonitorError>{
+        self.open_for_reading()?.monitor.read_data_inner(max_requests, Some(cancel)).map_err(CO2MonitorError::from_boxed)
+    }
+    /// Throw away `warmup_reads` readings, then take `actual_reads` and return the last one. 
+    ///
+    /// The sensor gives unreliable values for a while after it was powered on or reconnected.
+    /// The device is kept open for all reads. `10` is a good starting point for `warmup_reads`,
+    /// like in the python library. Every read polls the device at most [DEFAULT_MAX_REQUESTS]
+    /// times. `actual_reads` must be at least 1.
+    pub fn read_with_warmup(&mut self, warmup_reads: u32, actual_reads: u32) -> Result<CO2Reading, CO2MonitorError>{
+        if actual_reads == 0 {
+            return Err(CO2MonitorError::InvalidArgument("`actual_reads` must be at least 1".to_owned()));
+        }
+        let mut session = self.open_for_reading()?;
+        for _ in 0..warmup_reads {
+            session.read_data(DEFAULT_MAX_REQUESTS)?;
+        }
+        let mut reading = session.read_data(DEFAULT_MAX_REQUESTS)?;
+        for _ in 1..actual_reads {
+            reading = session.read_data(DEFAULT_MAX_REQUESTS)?;
+        }
+        Ok(reading)
+    }
+    /// Like [CO2Monitor::read_data] but neither opens nor closes the device. 
+    ///
+    /// Fails with [CO2MonitorError::NotOpen] if the device isn't open, see
+    /// [CO2Monitor::from_open_device].
+    pub fn read_data_open(&mut self, max_requests: u32) -> Result<CO2Reading, CO2MonitorError>{
+        self.read_data_inner(max_requests, None).map_err(CO2MonitorError::from_boxed)
+    }
+    /// Throw away the frames the device already sent but that weren't read yet, and return how
+    /// many there were. 
+    ///
+    /// While the device is open but not read from, the frames queue up and the next read returns
+    /// the oldest of them, which can be minutes old. Call this right before reading to get fresh
+    /// values. Does nothing if the device isn't open. [CO2Monitor::read_data] does this on its own
+    /// when the device is already open and `warmup_frames` is set on the
+    /// [CO2MonitorBuilder].
+    pub fn flush(&mut self) -> Result<u32, CO2MonitorError>{
+        let device = match &self.connection {
+            ConnectionState::Open(device) => device,
+            ConnectionState::Closed => return Ok(0),
+        };
+        let mut report : [u8;9] = [0;9];
+        let mut flushed = 0;
+        // the device keeps sending, so don't drain forever
+        while flushed < MAX_FLUSH_FRAMES {
+            if device.read_timeout(&mut report, 0)? == 0 {
+                break;
+            }
+            flushed += 1;
+        }
+        Ok(flushed)
+    }
+    /// Take `samples` readings and return their mean, see [CO2Reading::average]. 
+    ///
+    /// Gives a steadier number than a single reading, for example for a log line once a minute.
+    /// The device is kept open for all reads and each of them polls it at most
+    /// `max_requests_each` times. `samples` must be at least 1.
+    pub fn read_data_averaged(&mut self, samples: u32, max_requests_each: u32) -> Result<CO2Reading, CO2MonitorError>{
+        if samples == 0 {
+            return Err(CO2MonitorError::InvalidArgument("`samples` must be at least 1".to_owned()));
+        }
+        let mut session = self.open_for_reading()?;
+        let mut readings = Vec::with_capacity(samples as usize);
+        for _ in 0..samples {
+            readings.push(session.read_data(max_requests_each)?);
+        }
+        CO2Reading::average(&readings)
+            .ok_or_else(|| CO2MonitorError::InvalidReading(format!("Unable to average the {} readings", readings.len())))
+    }
+    /// Open the device once for several reads. 
+    ///
+    /// [CO2Monitor::read_data] opens and closes the device every time, which is slow if you read
+    /// often. The returned [CO2MonitorSession] keeps it open until it is dropped.
+    pub fn open_for_reading(&mut self) -> Result<CO2MonitorSession<'_>, Box<dyn Error>>{
+        CO2MonitorSession::open(self)
+    }
+    /// Lock the device so that other processes using this crate can't read from it at the same
+    /// time, which would interleave the frames and garble both readings. 
+    ///
+    /// Takes a lock file named after the interface path and the serial number in `/run/lock`, or
+    /// in the temp dir if that doesn't exist. Fails right away with
+    /// [CO2MonitorError::DeviceLocked] if someone else holds it. The lock is advisory, so it only
+    /// keeps out programs that take it as well.
+    pub fn open_exclusive(self) -> Result<CO2MonitorGuard, CO2MonitorError>{
+        CO2MonitorGuard::lock(self)
+    }
+    /// Like [CO2Monitor::read_data] but returns whatever it got within `max_requests`. 
+    ///
+    /// Only fails if neither the co2 nor the temperature could be read, so you don't lose the co2
+    /// when the device didn't get around to sending the temperature.
+    pub fn read_data_partial(&mut self, max_requests: u32) -> Result<PartialReading, CO2MonitorError>{
+        let partial = self.open_for_reading()?.monitor.read_partial_inner(max_requests, None)?;
+        if partial.co2_ppm.is_none() && partial.temp_c.is_none() {
+            return Err(not_read("co2 or the temperature"));
+        }
+        self.last_reading_at = Some(Instant::now());
+        Ok(partial)
+    }
+    /// Like [CO2Monitor::read_data] but also waits for the humidity. 
+    ///
+    /// Only some models report the humidity, on the others this fails once `max_requests` frames
+    /// were read. Use [CO2Monitor::read_data] if you just want the humidity when there is one.
+    pub fn read_full(&mut self, max_requests: u32) -> Result<FullReading, CO2MonitorError>{
+        let session = self.open_for_reading()?;
+        let decode = session.monitor.value_decoder();
+        let mut first = true;
+        let (co2, temp, humidity) = collect_values(max_requests, true, decode, || session.monitor.read_frame(&mut first, None))?;
+        drop(session);
+        let reading = FullReading {
+            co2_ppm: self.calibrated_co2(co2.ok_or_else(|| not_read("co2"))?)?,
+            temp_c: self.calibrated_temp(temp.ok_or_else(|| not_read("temperature"))?)?,
+            humidity_pct: humidity.ok_or_else(|| not_read("humidity"))?,
+        };
+        self.last_reading_at = Some(Instant::now());
+        Ok(reading)
+    }
+    /// Read only the co2, returning as soon as a co2 frame arrives instead of also waiting for the
+    /// temperature like [CO2Monitor::read_data]. Opens and closes the device. 
+    pub fn read_co2_only(&mut self, max_requests: u32) -> Result<CO2PPM, CO2MonitorError>{
+        let co2 = self.open_for_reading()?.monitor.read_first(max_requests, |(co2_val, _, _)| co2_val)?;
+        self.calibrated_co2(co2.ok_or_else(|| not_read("co2"))?)
+    }
+    /// Like [CO2Monitor::read_co2_only] for the temperature
+    pub fn read_temperature_only(&mut self, max_requests: u32) -> Result<TempCelsius, CO2MonitorError>{
+        let temp = self.open_for_reading()?.monitor.read_first(max_requests, |(_, temp_val, _)| temp_val)?;
+        self.calibrated_temp(temp.ok_or_else(|| not_read("temperature"))?)
+    }
+    // read until `pick` returns Some(..) for a frame, or give up after max_requests frames
+    fn read_first<T>(&mut self, max_requests: u32, pick: impl Fn(RawValues) -> Option<T>) -> Result<Option<T>, Box<dyn Error>>{
+        let decode = self.value_decoder();
+        let mut first = true;
+        for _ in 0..max_requests {
+            let data = self.read_frame(&mut first, None)?;
+            if let Some(value) = pick(decode(&data)) {
+                self.last_reading_at = Some(Instant::now());
+                return Ok(Some(value));
+            }
+        }
+        Ok(None)
+    }
+    /// Keep reading until the last `window` readings all have a co2 within `tolerance_ppm` of
+    /// each other and return their mean. 
+    ///
+    /// Handy right after plugging the device in, while the sensor is still warming up. A reading
+    /// is taken every time a co2 frame arrives (using the latest temperature), and at most
+    /// `max_total_requests` frames are read before giving up.
+    pub fn read_until_stable(&mut self, tolerance_ppm: u32, window: usize, max_total_requests: u32) -> Result<CO2Reading, CO2MonitorError>{
+        if window == 0 {
+            return Err(CO2MonitorError::InvalidArgument("`window` must be at least 1".to_owned()));
+        }
+        self.open_for_reading()?.monitor.read_until_stable_inner(tolerance_ppm, window, max_total_requests).map_err(CO2MonitorError::from_boxed)
+    }
+    fn read_until_stable_inner(&mut self, tolerance_ppm: u32, window: usize, max_total_requests: u32) -> Result<CO2Reading, Box<dyn Error>>{
+        let mut temp : Option<TempCelsius> = None;
+        let mut humidity : Option<f32> = None;
+        let mut readings : VecDeque<CO2Reading> = VecDeque::with_capacity(window);
+        let decode = self.value_decoder();
+        let mut first = true;
+        for _ in 0..max_total_requests {
+            let (co2_val, temp_val, humidity_val) = decode(&self.read_frame(&mut first, None)?);
+            if let Some(temp_val) = temp_val {
+                temp = Some(self.calibrated_temp(temp_val)?);
+            }
+            humidity = humidity_val.or(humidity);
+            // can't make a reading until the first temperature frame shows up
+            let (co2_val, temp_c) = match (co2_val, temp) {
+                (Some(co2_val), Some(temp_c)) => (co2_val, temp_c),
+                _ => continue,
+            };
+            if readings.len() == window {
+                readings.pop_front();
+            }
+            readings.push_back(CO2Reading { co2_ppm: self.calibrated_co2(co2_val)?, temp_c, humidity_pct: humidity });
+            if readings.len() == window {
+                if let Some(mean) = stable_mean(&readings, tolerance_ppm) {
+                    self.last_reading_at = Some(Instant::now());
+                    return Ok(mean);
+                }
+            }
+        }
+        Err("The readings did not stabilize in the allotted number of requests".into())
+    }
 }
 
 
+// a reading without humidity, for the tests of every module
+#[cfg(test)]
+pub(crate) fn reading(co2_ppm: u32, temp_c: f32) -> CO2Reading {
+    CO2Reading {
+        co2_ppm: CO2PPM::try_from(co2_ppm).unwrap(),
+        temp_c: TempCelsius::try_from(temp_c).unwrap(),
+        humidity_pct: None,
+    }
+}
+
 #[cfg(test)]
 mod tests{
     use crate::*;
+    use crate::codec::convert_temperature_to_celcius;
     use serial_test::serial;
 
     #[test]
     fn compilation() {
         assert_eq!(1+1,2);
     }
+    fn assert_close(a: f32, b: f32) {
+        assert!((a - b).abs() < 0.001, "{} != {}", a, b);
+    }
+    fn sample_info() -> CO2MonitorInfo {
+        CO2MonitorInfo {
+            vendor_id: 0x04d9,
+            product_id: 0xa052,
+            path: "1-13:1.0".to_owned(),
+            manufacturer: "Holtek".to_owned(),
+            product_name: "USB-zyTemp".to_owned(),
+            serial_no: "2.00".to_owned(),
+            release_number: 0x0200,
+        }
+    }
+    #[test]
+    fn percent_change() {
+        let baseline = reading(800, 20.0);
+        assert_eq!(reading(1000, 20.0).percent_change_from(&baseline), 25.0);
+        assert_eq!(reading(600, 20.0).percent_change_from(&baseline), -25.0);
+        assert_eq!(baseline.co2_ppm_f64(), 800.0);
+    }
+    #[test]
+    fn json_string() {
+        assert_eq!(reading(800, 21.5).to_json_string(), r#"{"co2_ppm":800,"temp_c":21.5}"#);
+        assert_eq!(reading(800, 20.0).to_json_string(), r#"{"co2_ppm":800,"temp_c":20.0}"#);
+    }
+    // decrypted frames: 800 ppm, 26.85°C, 45% and a co2 frame with a bad checksum
+    const CO2 : [u8;8] = [0x50, 0x03, 0x20, 0x73, 0x0D, 0, 0, 0];
+    const TEMP : [u8;8] = [0x42, 0x12, 0xC0, 0x14, 0x0D, 0, 0, 0];
+    const HUMIDITY : [u8;8] = [0x41, 0x11, 0x94, 0xE6, 0x0D, 0, 0, 0];
+    const BAD : [u8;8] = [0x50, 0x03, 0x20, 0x74, 0x0D, 0, 0, 0];
+    // feeds the frames in order and fails once they run out
+    fn scripted(frames: &[[u8;8]]) -> impl FnMut() -> Result<[u8;8], Box<dyn Error>> + '_ {
+        let mut frames = frames.iter();
+        move || frames.next().copied().ok_or_else(|| "Out of frames".into())
+    }
+    #[test]
+    fn collect_values_stops_early() {
+        // stops as soon as both are there instead of reading the remaining frames
+        let (co2, temp, _) = collect_values(50, false, decode_values, scripted(&[CO2, TEMP])).unwrap();
+        assert_eq!(co2, Some(800));
+        assert!(temp.is_some());
+        // both show up on the very last allowed frame. This used to keep reading forever
+        let (co2, temp, _) = collect_values(3, false, decode_values, scripted(&[BAD, CO2, TEMP])).unwrap();
+        assert_eq!(co2, Some(800));
+        assert!(temp.is_some());
+        // gives up after max_requests frames
+        let (co2, temp, _) = collect_values(2, false, decode_values, scripted(&[CO2, BAD, TEMP])).unwrap();
+        assert_eq!(co2, Some(800));
+        assert!(temp.is_none());
+        assert_eq!(collect_values(0, false, decode_values, scripted(&[])).unwrap(), (None, None, None));
+    }
+    #[test]
+    fn collect_values_with_humidity() {
+        let (co2, temp, humidity) = collect_values(50, true, decode_values, scripted(&[CO2, TEMP, HUMIDITY])).unwrap();
+        assert_eq!((co2, humidity), (Some(800), Some(45.0)));
+        assert!(temp.is_some());
+        // runs out of requests waiting for the humidity
+        let (_, _, humidity) = collect_values(3, true, decode_values, scripted(&[CO2, TEMP, CO2])).unwrap();
+        assert!(humidity.is_none());
+    }
+    #[test]
+    fn stable_mean_test() {
+        let mut readings : VecDeque<CO2Reading> = vec![reading(800, 21.0), reading(810, 22.0), reading(805, 21.5)].into();
+        let mean = stable_mean(&readings, 10).unwrap();
+        assert_eq!(mean.co2_ppm.get(), 805);
+        assert_close(mean.temp_c.get(), 21.5);
+        assert!(mean.humidity_pct.is_none());
+        assert!(stable_mean(&readings, 9).is_none());
+        readings.clear();
+        assert!(stable_mean(&readings, 10).is_none());
+    }
+    #[test]
+    fn collect_values_custom_decoder() {
+        // a made up device that sends the co2 in the first two bytes and the temperature in the
+        // next two when the last byte is set
+        let decoder = CustomDecoder::new(|frame| match frame[7] {
+            1 => (Some(u16::from_be_bytes([frame[0], frame[1]]) as u32), None),
+            2 => (None, Some(u16::from_be_bytes([frame[0], frame[1]]) as f32 / 10.0)),
+            _ => (None, None),
+        });
+        let decode = |data: &[u8;8]| {
+            let (co2, temp) = decoder.decode(*data);
+            (co2, temp, None)
+        };
+        let frames = [[0x03, 0x20, 0, 0, 0, 0, 0, 1], CO2, [0x00, 0xD7, 0, 0, 0, 0, 0, 2]];
+        let (co2, temp, humidity) = collect_values(50, false, decode, scripted(&frames)).unwrap();
+        assert_eq!(co2, Some(800));
+        assert_close(temp.unwrap(), 21.5);
+        assert_eq!(humidity, None);
+    }
+    #[test]
+    fn averages() {
+        assert!(CO2Reading::average(&[]).is_none());
+        let readings = [reading(800, 21.0), CO2Reading { humidity_pct: Some(40.0), ..reading(811, 22.0) }];
+        let mean = CO2Reading::average(&readings).unwrap();
+        assert_eq!(mean.co2_ppm.get(), 806);
+        assert_close(mean.temp_c.get(), 21.5);
+        assert_eq!(mean.humidity_pct, Some(40.0));
+        let weighted = [(readings[0].clone(), 0.75), (readings[1].clone(), 0.25)];
+        let mean = CO2Reading::weighted_average(&weighted).unwrap();
+        assert_eq!(mean.co2_ppm.get(), 803);
+        assert_close(mean.temp_c.get(), 21.25);
+        let weighted = [(readings[0].clone(), 0.75), (readings[1].clone(), 0.5)];
+        assert!(CO2Reading::weighted_average(&weighted).is_none());
+        let weighted = [(readings[0].clone(), 1.5), (readings[1].clone(), -0.5)];
+        assert!(CO2Reading::weighted_average(&weighted).is_none());
+    }
+    #[test]
+    fn locale_formatting() {
+        let r = reading(1234, 21.3);
+        assert_eq!(r.fmt_with_locale(',', Some('.')), "CO2: 1.234 ppm | Temp: 21,3°C");
+        assert_eq!(r.fmt_with_locale('.', None), "CO2: 1234 ppm | Temp: 21.3°C");
+        let r = CO2Reading { humidity_pct: Some(45.0), ..reading(812, -5.3125) };
+        assert_eq!(r.fmt_with_locale(',', Some(' ')), "CO2: 812 ppm | Temp: -5,3°C | Humidity: 45,0%");
+        assert_eq!(reading(1_000_000, 0.0).fmt_with_locale('.', Some(',')), "CO2: 1,000,000 ppm | Temp: 0.0°C");
+    }
+    #[test]
+    #[cfg(feature="color")]
+    fn colored_formatting() {
+        let r = reading(1234, 21.3);
+        assert_eq!(r.fmt_colored_if(false), r.fmt_with_locale('.', None));
+        assert_eq!(r.fmt_colored_if(true), "CO2: \x1b[38;5;208m1234 ppm\x1b[0m | Temp: \x1b[36m21.3°C\x1b[0m");
+        assert!(reading(799, 20.0).fmt_colored_if(true).contains("\x1b[32m799 ppm"));
+        assert!(reading(1600, 20.0).fmt_colored_if(true).contains("\x1b[31m1600 ppm"));
+    }
+    #[test]
+    #[cfg(feature="cbor")]
+    fn cbor_round_trip() {
+        let bytes = reading(812, 21.3125).to_cbor_bytes().unwrap();
+        // {1: 812, 2: 21.3125} with the temperature as a half float
+        assert_eq!(bytes, [0xa2, 0x01, 0x19, 0x03, 0x2c, 0x02, 0xf9, 0x4d, 0x54]);
+        let back = CO2Reading::from_cbor_bytes(&bytes).unwrap();
+        assert_eq!(back.co2_ppm.get(), 812);
+        assert_eq!(back.temp_c.get(), 21.3125);
+        assert!(back.humidity_pct.is_none());
+        let r = CO2Reading { humidity_pct: Some(45.5), ..reading(812, -5.3) };
+        let back = CO2Reading::from_cbor_bytes(&r.to_cbor_bytes().unwrap()).unwrap();
+        assert_eq!(back.temp_c.get(), -5.3);
+        assert_eq!(back.humidity_pct, Some(45.5));
+        // {0: 1700000000, 1: 812, 2: 21.3125}, the timestamp is ignored
+        let stamped = [0xa3, 0x00, 0x1a, 0x65, 0x53, 0xf1, 0x00, 0x01, 0x19, 0x03, 0x2c, 0x02, 0xf9, 0x4d, 0x54];
+        assert_eq!(CO2Reading::from_cbor_bytes(&stamped).unwrap().co2_ppm.get(), 812);
+        // {1: 812}
+        assert!(matches!(CO2Reading::from_cbor_bytes(&[0xa1, 0x01, 0x19, 0x03, 0x2c]), Err(CO2MonitorError::InvalidReading(_))));
+        assert!(CO2Reading::from_cbor_bytes(&[0x01]).is_err());
+    }
+    #[test]
+    fn registered_devices() {
+        // no device has this id, so the tests using the real one are not affected
+        register_known_device(0xffff, 0xfffe);
+        register_known_device(0xffff, 0xfffe);
+        let known = known_devices();
+        assert_eq!(known[0], (CO2MON_HID_VENDOR_ID, CO2MON_HID_PRODUCT_ID));
+        assert_eq!(known.iter().filter(|&&id| id == (0xffff, 0xfffe)).count(), 1);
+    }
+    #[test]
+    fn bytes_round_trip() {
+        let time = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let bytes = reading(847, -5.3125).to_bytes_with_time(Some(time));
+        assert_eq!(bytes[..8], 847_u64.to_le_bytes());
+        assert_eq!(bytes[8..12], (-5.3125_f32).to_le_bytes());
+        assert_eq!(bytes[12..], 1_700_000_000_u32.to_le_bytes());
+        let (back, back_time) = CO2Reading::from_bytes_with_time(&bytes).unwrap();
+        assert_eq!(back.co2_ppm.get(), 847);
+        assert_eq!(back.temp_c.get(), -5.3125);
+        assert_eq!(back_time, Some(time));
+        // no timestamp, and the humidity isn't stored
+        let bytes = CO2Reading { humidity_pct: Some(45.5), ..reading(400, 20.0) }.to_bytes();
+        assert_eq!(bytes[12..], [0xFF; 4]);
+        assert_eq!(CO2Reading::from_bytes_with_time(&bytes).unwrap().1, None);
+        assert!(CO2Reading::from_bytes(&bytes).unwrap().humidity_pct.is_none());
+        let mut bytes = [0_u8;16];
+        bytes[..8].copy_from_slice(&(u32::MAX as u64 + 1).to_le_bytes());
+        assert!(CO2Reading::from_bytes(&bytes).is_err());
+    }
+    #[test]
+    fn journald_fields() {
+        let fields = CO2Reading { humidity_pct: Some(45.5), ..reading(847, 21.3) }.to_journald_fields();
+        let fields : Vec<(&str, &str)> = fields.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+        assert_eq!(fields, vec![("CO2_PPM", "847"), ("TEMP_C", "21.3"), ("HUMIDITY_PCT", "45.5")]);
+    }
+    #[test]
+    fn openmetrics_string() {
+        let r = reading(812, 21.0);
+        assert_eq!(r.to_openmetrics_string("office", &[("room", "a\"b"), ("floor", "2")]).unwrap(), concat!(
+            "# TYPE office_co2_ppm gauge\n",
+            "# HELP office_co2_ppm CO2 concentration in parts per million\n",
+            "office_co2_ppm{room=\"a\\\"b\",floor=\"2\"} 812\n",
+            "# TYPE office_temp_c gauge\n",
+            "# HELP office_temp_c Temperature in degrees celsius\n",
+            "office_temp_c{room=\"a\\\"b\",floor=\"2\"} 21.0\n",
+            "# EOF\n",
+        ));
+        assert!(r.to_openmetrics_string("", &[]).unwrap().starts_with("# TYPE co2_ppm gauge\n# HELP co2_ppm CO2 concentration in parts per million\nco2_ppm 812\n"));
+        assert!(r.to_openmetrics_string("office-1", &[]).is_err());
+        assert!(r.to_openmetrics_string("office", &[("1room", "a")]).is_err());
+    }
+    #[test]
+    fn graphite_metric() {
+        let r = reading(812, 21.5);
+        assert_eq!(r.to_graphite_metric("office.co2-meter_1", Some(1660000000)).unwrap(),
+            "office.co2-meter_1.co2_ppm 812 1660000000\noffice.co2-meter_1.temp_c 21.5 1660000000\n");
+        assert!(r.to_graphite_metric("office co2", Some(0)).is_err());
+        assert!(r.to_graphite_metric("", Some(0)).is_err());
+        assert!(r.to_graphite_metric("office", None).unwrap().starts_with("office.co2_ppm 812 "));
+    }
+    #[test]
+    fn statsd_gauges() {
+        assert_eq!(reading(812, 21.5).to_statsd_gauge_lines("office"), "office.co2_ppm:812|g\noffice.temp_c:21.5|g");
+        let r = CO2Reading { humidity_pct: Some(45.5), ..reading(812, 21.5) };
+        assert!(r.to_statsd_gauge_lines("office").ends_with("|g\noffice.humidity_pct:45.5|g"));
+        assert_eq!(reading(812, -5.25).to_statsd_gauge_lines("office"), "office.co2_ppm:812|g\noffice.temp_c:0|g\noffice.temp_c:-5.25|g");
+    }
+    #[test]
+    fn dew_point() {
+        assert_eq!(reading(800, 20.0).dew_point_c(), None);
+        let mut r = reading(800, 20.0);
+        r.humidity_pct = Some(100.0);
+        assert!((r.dew_point_c().unwrap() - 20.0).abs() < 0.01);
+        r.humidity_pct = Some(50.0);
+        assert!((r.dew_point_c().unwrap() - 9.26).abs() < 0.05);
+        assert_eq!(
+            r.to_json_string(),
+            r#"{"co2_ppm":800,"temp_c":20.0,"humidity_pct":50.0}"#
+        );
+    }
+    #[test]
+    fn mass_concentration() {
+        let r = reading(1000, 20.0);
+        assert!((r.mg_per_m3() - 1798.9).abs() < 0.1);
+        // STP
+        assert!((r.mg_per_m3_at_conditions(0.0, 1013.25) - 1963.5).abs() < 0.1);
+    }
+    #[test]
+    fn info_in_hash_set() {
+        let info = sample_info();
+        let mut seen = std::collections::HashSet::new();
+        assert!(seen.insert(info.clone()));
+        assert!(!seen.insert(info.clone()));
+        assert!(seen.insert(CO2MonitorInfo { path: "1-14:1.0".to_owned(), ..info }));
+    }
+    #[test]
+    fn device_matching() {
+        let info = sample_info();
+        let other_port = CO2MonitorInfo { path: "1-14:1.0".to_owned(), ..sample_info() };
+        assert!(info.matches_device_info(&other_port));
+        assert!(!info.is_same_port(&other_port));
+        assert!(info.is_same_port(&sample_info()));
+        let other_device = CO2MonitorInfo { serial_no: "2.01".to_owned(), ..sample_info() };
+        assert!(!info.matches_device_info(&other_device));
+        assert!(!info.is_same_port(&other_device));
+    }
+    #[test]
+    fn udev_rule() {
+        let info = sample_info();
+        assert_eq!(info.to_udev_rule("plugdev").unwrap(),
+            r#"SUBSYSTEM=="usb", ATTR{idVendor}=="04d9", ATTR{idProduct}=="a052", GROUP="plugdev", MODE="0660""#);
+        assert!(info.to_udev_rule("plugdev\", MODE=\"0666").is_err());
+        assert!(info.to_udev_rule("").is_err());
+    }
+    #[test]
+    fn interpolation() {
+        let a = reading(800, 20.0);
+        let b = CO2Reading { humidity_pct: Some(50.0), ..reading(900, 22.0) };
+        let mid = a.interpolate(&b, 0.25);
+        assert_eq!(mid.co2_ppm.get(), 825);
+        assert_close(mid.temp_c.get(), 20.5);
+        assert!(mid.humidity_pct.is_none());
+        assert_eq!(a.interpolate(&b, 2.0).co2_ppm.get(), 900);
+        assert_eq!(a.interpolate(&b, f64::NAN).co2_ppm.get(), 800);
+        // going down works too
+        assert_eq!(b.interpolate(&a, 0.5).co2_ppm.get(), 850);
+    }
+    #[test]
+    fn sea_level_normalization() {
+        let r = reading(800, 20.0);
+        assert_eq!(r.normalize_to_sea_level(0.0).unwrap().co2_ppm.get(), 800);
+        assert_eq!(r.normalize_to_sea_level(1000.0).unwrap().co2_ppm.get(), 902);
+        assert_eq!(r.co2_ppm.get(), 800);
+        assert!(r.normalize_to_sea_level(-1.0).is_err());
+        assert!(r.normalize_to_sea_level(f32::NAN).is_err());
+    }
+    #[test]
+    fn frame_u64_round_trip() {
+        let frame = [0x01, 0x23, 0x45, 0x67, 0x89, 0xAB, 0xCD, 0xEF];
+        assert_eq!(frame_to_u64_be(&frame), 0x0123_4567_89AB_CDEF);
+        assert_eq!(u64_to_frame_be(0x0123_4567_89AB_CDEF), frame);
+        assert_eq!(frame_to_u64_le(&frame), 0xEFCD_AB89_6745_2301);
+        assert_eq!(u64_to_frame_le(0xEFCD_AB89_6745_2301), frame);
+        // every single set bit, then pseudo random values from a xorshift generator
+        let mut values : Vec<u64> = vec![0, u64::MAX];
+        values.extend((0..64).map(|bit| 1_u64 << bit));
+        let mut x : u64 = 0x9E37_79B9_7F4A_7C15;
+        for _ in 0..10_000 {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            values.push(x);
+        }
+        for value in values {
+            assert_eq!(frame_to_u64_be(&u64_to_frame_be(value)), value);
+            assert_eq!(frame_to_u64_le(&u64_to_frame_le(value)), value);
+            let frame = value.to_ne_bytes();
+            assert_eq!(u64_to_frame_be(frame_to_u64_be(&frame)), frame);
+            assert_eq!(u64_to_frame_le(frame_to_u64_le(&frame)), frame);
+        }
+    }
+    #[test]
+    fn temperature_conversions() {
+        // absolute zero
+        assert_close(convert_temperature_to_celcius(0), -273.15);
+        assert_close(convert_temperature_to_kelvin(0), 0.0);
+        assert_close(convert_temperature_to_fahrenheit(0), -459.67);
+        // 4800/16 = 300K
+        assert_close(convert_temperature_to_celcius(4800), 26.85);
+        assert_close(convert_temperature_to_kelvin(4800), 300.0);
+        assert_close(convert_temperature_to_fahrenheit(4800), 80.33);
+        // -40 is the same in celcius and fahrenheit. 233.15K * 16 = 3730.4, so use the closest raw value
+        assert_close(convert_temperature_to_celcius(3730), -40.025);
+        assert_eq!(convert_temperature_to_decikelvin(4800), 3000);
+        assert_eq!(convert_temperature_to_decikelvin(4701), 2938);
+        assert_eq!(convert_temperature_to_decikelvin(u16::MAX), 40959);
+        for raw in [0, 3730, 4701, 4800, 5000] {
+            let r = reading(400, convert_temperature_to_celcius(raw));
+            assert_eq!(r.temp_decikelvin(), convert_temperature_to_decikelvin(raw));
+        }
+        assert_close(convert_temperature_to_fahrenheit(3730), -40.045);
+        // largest raw value
+        assert_close(convert_temperature_to_kelvin(u16::MAX), 4095.9375);
+    }
+    #[test]
+    fn find_device_in_list() {
+        let device = |vendor_id, product_id, path| DeviceIds { vendor_id, product_id, path, serial: None };
+        let devices = [
+            device(0x1234, 0x5678, c"1-1:1.0"),
+            device(CO2MON_HID_VENDOR_ID, CO2MON_HID_PRODUCT_ID, c"1-13:1.0"),
+            device(CO2MON_HID_VENDOR_ID, CO2MON_HID_PRODUCT_ID, c"1-14:1.0"),
+        ];
+        // the first one with a known id
+        assert_eq!(CO2Monitor::find_device(devices.iter(), None).unwrap(), &devices[1]);
+        assert_eq!(CO2Monitor::find_device(devices.iter(), Some("1-14:1.0")).unwrap(), &devices[2]);
+        assert!(matches!(CO2Monitor::find_device(devices.iter(), Some("1-1:1.0")), Err(CO2MonitorError::PathNotFound(_))));
+        assert!(matches!(CO2Monitor::find_device(devices[..1].iter(), None), Err(CO2MonitorError::DeviceNotFound)));
+        assert!(matches!(CO2Monitor::find_device(devices[..1].iter(), Some("1-1:1.0")), Err(CO2MonitorError::DeviceNotFound)));
+    }
     #[test]
     #[serial]
     fn find_device() {
@@ -291,6 +2022,26 @@ mod tests{
         assert_eq!(co2.device_info.product_id(), CO2MON_HID_PRODUCT_ID);
     }
     #[test]
+    #[cfg(feature="regex")]
+    fn invalid_path_pattern() {
+        let err = CO2MonitorBuilder::new().path_pattern("1-[0-9").build().err().unwrap();
+        assert!(matches!(err.downcast_ref::<CO2MonitorError>(), Some(CO2MonitorError::InvalidArgument(_))));
+    }
+    #[test]
+    #[serial]
+    #[cfg(feature="regex")]
+    fn path_pattern_test(){
+        let path = CO2Monitor::default().unwrap().info().path;
+        let co2 = CO2MonitorBuilder::new().path_pattern(&format!("^{}$", regex::escape(&path))).build().unwrap();
+        assert_eq!(co2.info().path, path);
+    }
+    #[test]
+    #[serial]
+    fn wrong_path_test(){
+        let err = CO2Monitor::new(false, Some("not-a-path".to_owned())).err().unwrap();
+        assert!(matches!(err.downcast_ref::<CO2MonitorError>(), Some(CO2MonitorError::PathNotFound(_))));
+    }
+    #[test]
     #[serial]
     fn read_message(){
         let mut co2 = CO2Monitor::default().unwrap();
@@ -299,11 +2050,206 @@ mod tests{
     }
     #[test]
     #[serial]
+    fn read_partial_message(){
+        let mut co2 = CO2Monitor::default().unwrap();
+        let result = co2.read_data_partial( 50).unwrap();
+        assert!(result.co2_ppm.is_some() || result.temp_c.is_some());
+        dbg!(result);
+    }
+    #[test]
+    #[serial]
+    fn read_session(){
+        let mut co2 = CO2Monitor::default().unwrap();
+        let mut session = co2.open_for_reading().unwrap();
+        dbg!(session.read_data( 50).unwrap());
+        dbg!(session.read_data( 50).unwrap());
+        drop(session);
+        dbg!(co2.read_data( 50).unwrap());
+    }
+    #[test]
+    #[serial]
+    fn readings_iterator(){
+        let co2 = CO2MonitorBuilder::new().warmup_frames(3).build().unwrap();
+        let readings : Vec<CO2Reading> = co2.boxed_readings(50).take(2).map(|r| r.unwrap()).collect();
+        assert_eq!(readings.len(), 2);
+    }
+    #[test]
+    #[serial]
+    fn spawn_into_channel_test(){
+        let co2 = CO2Monitor::default().unwrap();
+        let (handle, receiver) = co2.spawn_into_channel(Duration::from_millis(100));
+        dbg!(receiver.recv().unwrap().unwrap());
+        drop(receiver);
+        handle.join().unwrap();
+    }
+    #[test]
+    #[serial]
+    fn read_single_values(){
+        let mut co2 = CO2Monitor::default().unwrap();
+        dbg!(co2.read_co2_only( 50).unwrap());
+        dbg!(co2.read_temperature_only( 50).unwrap());
+    }
+    #[test]
+    #[serial]
+    fn read_cancelled(){
+        let mut co2 = CO2Monitor::default().unwrap();
+        let err = co2.read_data_cancellable( 50, &AtomicBool::new(true)).err().unwrap();
+        assert!(matches!(err, CO2MonitorError::Cancelled));
+        dbg!(co2.read_data_cancellable( 50, &AtomicBool::new(false)).unwrap());
+    }
+    #[test]
+    #[serial]
+    fn last_reading_age_test(){
+        let mut co2 = CO2Monitor::default().unwrap();
+        assert!(co2.last_reading_age().is_none());
+        co2.read_data(DEFAULT_MAX_REQUESTS).unwrap();
+        assert!(co2.last_reading_age().unwrap() < Duration::from_secs(1));
+        // the other reads count too
+        let mut assert_refreshed = |read: &dyn Fn(&mut CO2Monitor)| {
+            thread::sleep(Duration::from_millis(10));
+            let before = co2.last_reading_age().unwrap();
+            read(&mut co2);
+            assert!(co2.last_reading_age().unwrap() < before);
+        };
+        assert_refreshed(&|co2| {co2.read_data_partial(DEFAULT_MAX_REQUESTS).unwrap();});
+        assert_refreshed(&|co2| {co2.read_co2_only(DEFAULT_MAX_REQUESTS).unwrap();});
+        assert_refreshed(&|co2| {co2.read_temperature_only(DEFAULT_MAX_REQUESTS).unwrap();});
+        assert_refreshed(&|co2| {co2.read_until_stable(1000, 1, DEFAULT_MAX_REQUESTS).unwrap();});
+    }
+    #[test]
+    #[serial]
+    fn read_message_with_warmup(){
+        let mut co2 = CO2MonitorBuilder::new().warmup_frames(3).build().unwrap();
+        let result = co2.read_data( 50);
+        dbg!(result.unwrap());
+    }
+    #[test]
+    #[serial]
+    fn read_with_warmup_test(){
+        let mut co2 = CO2Monitor::default().unwrap();
+        assert!(matches!(co2.read_with_warmup(1, 0), Err(CO2MonitorError::InvalidArgument(_))));
+        dbg!(co2.read_with_warmup(2, 2).unwrap());
+    }
+    #[test]
+    #[serial]
+    fn without_magic_table(){
+        // the device still has the table from the tests before
+        let mut co2 = CO2MonitorBuilder::new().send_magic_table(false).build().unwrap();
+        dbg!(co2.read_data(DEFAULT_MAX_REQUESTS).unwrap());
+    }
+    #[test]
+    #[serial]
+    fn flush_test(){
+        let mut co2 = CO2Monitor::default().unwrap();
+        assert_eq!(co2.flush().unwrap(), 0);
+        let mut session = co2.open_for_reading().unwrap();
+        std::thread::sleep(Duration::from_secs(3));
+        assert!(session.flush().unwrap() > 0);
+        dbg!(session.read_data(DEFAULT_MAX_REQUESTS).unwrap());
+    }
+    #[test]
+    #[serial]
+    fn read_from_open_device(){
+        let hid = HidApi::new().unwrap();
+        let device_info = hid.device_list().find(|device| is_co2_monitor(device)).unwrap().clone();
+        let device = device_info.open_device(&hid).unwrap();
+        device.send_feature_report(&[0;8]).unwrap();
+        let mut co2 = CO2Monitor::from_open_device(device, device_info, false);
+        dbg!(co2.read_data_open( 50).unwrap());
+        dbg!(co2.read_data_open( 50).unwrap());
+    }
+    #[test]
+    #[serial]
+    fn debug_enumerate_test(){
+        let devices = CO2Monitor::debug_enumerate().unwrap();
+        assert!(devices.iter().any(|device| device.vendor_id == 0x04d9 && device.product_id == 0xa052));
+        dbg!(devices);
+    }
+    #[test]
+    #[serial]
+    fn read_lowpower_test(){
+        let mut co2 = CO2Monitor::default().unwrap();
+        dbg!(co2.read_lowpower( 50).unwrap());
+        assert!(matches!(co2.connection, ConnectionState::Closed));
+    }
+    #[test]
+    #[serial]
+    fn read_data_averaged_test(){
+        let mut co2 = CO2Monitor::default().unwrap();
+        assert!(matches!(co2.read_data_averaged(0, 50), Err(CO2MonitorError::InvalidArgument(_))));
+        dbg!(co2.read_data_averaged(3, 50).unwrap());
+    }
+    #[test]
+    #[serial]
+    fn read_message_with_offsets(){
+        let mut co2 = CO2MonitorBuilder::new().temp_offset_c(-1.5).co2_offset_ppm(-20).build().unwrap();
+        let result = co2.read_data( 50);
+        dbg!(result.unwrap());
+    }
+    #[test]
+    #[serial]
     fn get_info_test(){
         let co2 = CO2Monitor::default().unwrap();
         let info = co2.info();
         dbg!(info);
 
     }
+    #[test]
+    #[serial]
+    fn get_info_borrowed_test(){
+        let co2 = CO2Monitor::default().unwrap();
+        let info = co2.get_info_borrowed();
+        assert_eq!(info.to_owned(), co2.info());
+        dbg!(info);
+    }
+    #[test]
+    #[serial]
+    fn get_info_extended_test(){
+        let co2 = CO2Monitor::default().unwrap();
+        let info = co2.get_info_extended();
+        assert_eq!(info.info.vendor_id, CO2MON_HID_VENDOR_ID);
+        dbg!(info);
+    }
+    #[test]
+    #[serial]
+    fn try_clone_test(){
+        let co2 = CO2Monitor::default().unwrap();
+        let mut copy = co2.try_clone().unwrap();
+        dbg!(copy.read_data(50).unwrap());
+    }
+    #[test]
+    #[serial]
+    fn is_device_present_test(){
+        let co2 = CO2Monitor::default().unwrap();
+        assert!(co2.is_device_present());
+    }
+    #[test]
+    #[serial]
+    fn find_all_test(){
+        let monitors = CO2Monitor::find_all(false).unwrap();
+        assert!(!monitors.is_empty());
+        let count = monitors.len();
+        for mut co2 in monitors {
+            dbg!(co2.read_data(50).unwrap());
+        }
+        let results = CO2Monitor::find_all_results(false).unwrap();
+        assert_eq!(results.len(), count);
+        assert!(results.iter().all(Result::is_ok));
+    }
+    #[test]
+    #[serial]
+    fn find_by_serial_test(){
+        let serial = CO2Monitor::default().unwrap().info().serial_no;
+        let co2 = CO2Monitor::find_by_serial(&serial, false).unwrap();
+        assert_eq!(co2.info().serial_no, serial);
+    }
+    #[test]
+    #[serial]
+    fn find_by_manufacturer_test(){
+        let manufacturer = CO2Monitor::default().unwrap().info().manufacturer;
+        let co2 = CO2Monitor::find_by_manufacturer(&manufacturer.to_uppercase(), false).unwrap();
+        assert_eq!(co2.info().manufacturer, manufacturer);
+        assert!(CO2Monitor::find_by_manufacturer("not a manufacturer", false).is_err());
+    }
 
 }
@@ -0,0 +1,100 @@
+use std::fmt;
+use std::sync::Arc;
+
+use crate::codec::{decode_message, DecodeError, MessageKind};
+use crate::CO2MonitorError;
+
+/// Where the parts of a decrypted frame are, for devices whose firmware orders them differently. 
+///
+/// Each field is the index of that byte in the 8 byte frame. The bytes that are not named are
+/// padding and must be zero. The default is the layout most devices use: code, value (high byte
+/// first), checksum and end of message code in the first 5 bytes, followed by 3 bytes of padding.
+/// Pass your own to [CO2MonitorBuilder::frame_layout](crate::CO2MonitorBuilder::frame_layout).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameLayout {
+    code : usize,
+    value_high : usize,
+    value_low : usize,
+    checksum : usize,
+    end_message : usize,
+}
+
+impl Default for FrameLayout {
+    fn default() -> FrameLayout {
+        FrameLayout { code: 0, value_high: 1, value_low: 2, checksum: 3, end_message: 4 }
+    }
+}
+
+impl FrameLayout {
+    /// Fails with [CO2MonitorError::InvalidArgument] if an index is out of the frame or used twice
+    pub fn new(code: usize, value_high: usize, value_low: usize, checksum: usize, end_message: usize) -> Result<FrameLayout, CO2MonitorError> {
+        let layout = FrameLayout { code, value_high, value_low, checksum, end_message };
+        let positions = layout.positions();
+        for (i, position) in positions.iter().enumerate() {
+            if *position >= 8 || positions[..i].contains(position) {
+                return Err(CO2MonitorError::InvalidArgument(format!("{:?} doesn't fit in an 8 byte frame", positions)));
+            }
+        }
+        Ok(layout)
+    }
+    /// Decode a decrypted frame laid out like this
+    pub fn decode(&self, frame: &[u8;8]) -> Result<MessageKind, DecodeError> {
+        decode_message(&self.normalize(*frame))
+    }
+    fn positions(&self) -> [usize;5] {
+        [self.code, self.value_high, self.value_low, self.checksum, self.end_message]
+    }
+    // move the bytes to where the default layout has them, keeping the padding in order
+    pub(crate) fn normalize(&self, frame: [u8;8]) -> [u8;8] {
+        let positions = self.positions();
+        let mut normalized = [0_u8;8];
+        for (i, position) in positions.iter().enumerate() {
+            normalized[i] = frame[*position];
+        }
+        let padding = (0..8).filter(|i| !positions.contains(i));
+        for (byte, i) in normalized[5..].iter_mut().zip(padding) {
+            *byte = frame[i];
+        }
+        normalized
+    }
+}
+
+// a decoder set with CO2MonitorBuilder::decoder. Wrapped so that the builder can still derive
+// Debug
+#[derive(Clone)]
+pub(crate) struct CustomDecoder(Arc<DecoderFn>);
+// frame in, (co2, temperature) out
+type DecoderFn = dyn Fn([u8;8]) -> (Option<u32>, Option<f32>) + Send + Sync;
+
+impl CustomDecoder {
+    pub(crate) fn new(decoder: impl Fn([u8;8]) -> (Option<u32>, Option<f32>) + Send + Sync + 'static) -> CustomDecoder {
+        CustomDecoder(Arc::new(decoder))
+    }
+    pub(crate) fn decode(&self, frame: [u8;8]) -> (Option<u32>, Option<f32>) {
+        (self.0)(frame)
+    }
+}
+
+impl fmt::Debug for CustomDecoder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("CustomDecoder")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn custom_layout() {
+        let default = FrameLayout::default();
+        let frame = [0x50, 0x03, 0x20, 0x73, 0x0D, 0, 0, 0];
+        assert_eq!(default.normalize(frame), frame);
+        // padding first, end of message code last
+        let layout = FrameLayout::new(3, 4, 5, 6, 7).unwrap();
+        assert_eq!(layout.decode(&[0, 0, 0, 0x50, 0x03, 0x20, 0x73, 0x0D]), Ok(MessageKind::Co2(800)));
+        assert_eq!(layout.decode(&[0, 1, 0, 0x50, 0x03, 0x20, 0x73, 0x0D]), Err(DecodeError::InvalidEnd));
+        assert!(FrameLayout::new(0, 1, 2, 3, 8).is_err());
+        assert!(FrameLayout::new(0, 1, 1, 3, 4).is_err());
+    }
+}
@@ -0,0 +1,117 @@
+//! Publish readings to an MQTT broker, for example for Home Assistant
+//!
+//! Enabled with the `mqtt` feature. Every reading is published as the same JSON the `serde`
+//! derives of [CO2Reading](crate::CO2Reading) produce, like `{"co2_ppm":812,"temp_c":21.3125}`.
+//!
+//! ```ignore
+//! let config = MqttConfig {
+//!     broker_url: "mqtt://homeassistant.local:1883".to_owned(),
+//!     topic: "home/office/co2".to_owned(),
+//!     ..MqttConfig::default()
+//! };
+//! publish_loop(CO2Monitor::default()?, &config, &AtomicBool::new(false))?;
+//! ```
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use log::warn;
+use rumqttc::{Client, MqttOptions, QoS};
+
+use crate::{CO2Monitor, CO2MonitorError, DEFAULT_MAX_REQUESTS};
+
+// readings queued while the broker is unreachable. Newer ones are dropped after that
+const QUEUED_READINGS : usize = 10;
+// how long to wait before trying to reconnect to the broker
+const RECONNECT_DELAY : Duration = Duration::from_secs(5);
+
+/// Settings for [publish_loop]
+#[derive(Debug, Clone)]
+pub struct MqttConfig {
+    /// Like `mqtt://localhost:1883`. Only plain TCP is supported, not `mqtts`
+    pub broker_url : String,
+    pub client_id : String,
+    pub topic : String,
+    /// Time to wait between two readings
+    pub poll_interval : Duration,
+    /// Passed to [CO2Monitor::read_data]
+    pub max_requests : u32,
+}
+
+impl Default for MqttConfig {
+    fn default() -> Self {
+        MqttConfig {
+            broker_url: "mqtt://localhost:1883".to_owned(),
+            client_id: "co2meter-rs".to_owned(),
+            topic: "co2meter".to_owned(),
+            poll_interval: Duration::from_secs(10),
+            max_requests: DEFAULT_MAX_REQUESTS,
+        }
+    }
+}
+
+/// Read `monitor` every `config.poll_interval` and publish the readings to `config.topic` until
+/// `stop` is set. 
+///
+/// Only fails if `config.broker_url` can't be parsed. Failed reads and a lost connection to the
+/// broker are logged with `log::warn!` and the loop keeps going: the connection is retried every
+/// few seconds and the first 10 readings taken in the meantime are published once it is back.
+pub fn publish_loop(monitor: CO2Monitor, config: &MqttConfig, stop: &AtomicBool) -> Result<(), CO2MonitorError> {
+    let (client, mut connection) = Client::new(mqtt_options(config)?, QUEUED_READINGS);
+    // drives the connection, and reconnects when it is lost. Ends once the client is dropped
+    let connection_thread = thread::spawn(move || {
+        for event in connection.iter() {
+            if let Err(e) = event {
+                warn!("Lost the connection to the MQTT broker: {}", e);
+                thread::sleep(RECONNECT_DELAY);
+            }
+        }
+    });
+    for reading in monitor.readings(config.max_requests) {
+        if stop.load(Ordering::SeqCst) {
+            break;
+        }
+        match reading {
+            Ok(reading) => {
+                // serializing a reading can't fail
+                let payload = serde_json::to_vec(&reading).expect("Unable to serialize the reading");
+                if let Err(e) = client.try_publish(&config.topic, QoS::AtLeastOnce, false, payload) {
+                    warn!("Dropped a reading because the MQTT queue is full: {}", e);
+                }
+            },
+            Err(e) => warn!("Unable to read the co2 monitor: {}", e),
+        }
+        thread::sleep(config.poll_interval);
+    }
+    drop(client);
+    // the thread only panics if the mqtt client does
+    let _ = connection_thread.join();
+    Ok(())
+}
+
+fn mqtt_options(config: &MqttConfig) -> Result<MqttOptions, CO2MonitorError> {
+    // rumqttc only takes the client id from the query, which may already have other options
+    let separator = if config.broker_url.contains('?') { '&' } else { '?' };
+    let url = format!("{}{}client_id={}", config.broker_url, separator, config.client_id);
+    MqttOptions::parse_url(url)
+        .map_err(|e| CO2MonitorError::InvalidArgument(format!("Invalid broker url {}: {}", config.broker_url, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_broker_url() {
+        let options = mqtt_options(&MqttConfig::default()).unwrap();
+        assert_eq!(options.broker_address(), ("localhost".to_owned(), 1883));
+        assert_eq!(options.client_id(), "co2meter-rs");
+        let config = MqttConfig { broker_url: "localhost:1883".to_owned(), ..MqttConfig::default() };
+        assert!(matches!(mqtt_options(&config), Err(CO2MonitorError::InvalidArgument(_))));
+        let config = MqttConfig { broker_url: "mqtt://localhost:1883?keep_alive_secs=5".to_owned(), ..MqttConfig::default() };
+        let options = mqtt_options(&config).unwrap();
+        assert_eq!(options.keep_alive(), std::time::Duration::from_secs(5));
+        assert_eq!(options.client_id(), "co2meter-rs");
+    }
+}
@@ -0,0 +1,40 @@
+use std::error::Error;
+
+use crate::{CO2Monitor, CO2Reading};
+
+/// An endless iterator of readings, see [CO2Monitor::readings]
+///
+/// ```ignore
+/// for reading in CO2Monitor::default()?.readings(50).take(10) {
+///     dbg!(reading?);
+/// }
+/// ```
+pub struct CO2MonitorReadings {
+    monitor : CO2Monitor,
+    max_requests : u32,
+}
+
+impl CO2MonitorReadings {
+    pub(crate) fn new(monitor: CO2Monitor, max_requests: u32) -> CO2MonitorReadings {
+        CO2MonitorReadings { monitor, max_requests }
+    }
+    /// Get the monitor back. It is closed, like a freshly created one.
+    pub fn into_inner(mut self) -> CO2Monitor {
+        let _ = self.monitor.hid_close();
+        self.monitor
+    }
+}
+
+impl Iterator for CO2MonitorReadings {
+    type Item = Result<CO2Reading, Box<dyn Error>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let result = self.monitor.hid_open_for_reading()
+            .and_then(|_| self.monitor.read_data_inner(self.max_requests, None));
+        if result.is_err() {
+            // reopen on the next call in case the device went away
+            let _ = self.monitor.hid_close();
+        }
+        Some(result)
+    }
+}
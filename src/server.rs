@@ -0,0 +1,174 @@
+//! Share one device between processes over a Unix domain socket
+//!
+//! Enabled with the `server` feature (Unix only). A [CO2MonitorServer] owns the [CO2Monitor] and
+//! sends every reading as a line of JSON to all connected clients. [CO2MonitorClient] reads them
+//! back, so processes without access to the USB device (like unprivileged containers) can still
+//! get readings.
+//!
+//! ```ignore
+//! // on the host
+//! let server = CO2MonitorServer::listen(Path::new("/run/co2.sock"), CO2Monitor::default()?, Duration::from_secs(10))?;
+//! // anywhere else
+//! let mut client = CO2MonitorClient::connect(Path::new("/run/co2.sock"))?;
+//! let reading = client.read_latest()?;
+//! ```
+
+use std::fs;
+use std::io::{self, BufRead, BufReader, ErrorKind, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::json::{reading_to_value, value_to_reading};
+use crate::{CO2Monitor, CO2MonitorError, CO2Reading, DEFAULT_MAX_REQUESTS};
+
+// a client that doesn't take a reading within this long is dropped, so that it can't stall the
+// others
+const CLIENT_WRITE_TIMEOUT : Duration = Duration::from_secs(1);
+
+/// Reads a [CO2Monitor] on a background thread and sends the readings to every client connected
+/// to a Unix socket.
+///
+/// Each reading is one line of JSON as produced by [reading_to_value]. A client that connects gets
+/// the latest reading straight away. Clients that stop reading are disconnected.
+pub struct CO2MonitorServer {
+    path : PathBuf,
+    stop : Arc<AtomicBool>,
+    handle : JoinHandle<()>,
+}
+
+impl CO2MonitorServer {
+    /// Bind the socket at `path` and start reading the device every `interval`.
+    ///
+    /// Fails if the socket can't be bound, for example because `path` already exists.
+    pub fn listen(path: &Path, monitor: CO2Monitor, interval: Duration) -> Result<CO2MonitorServer, CO2MonitorError> {
+        let listener = UnixListener::bind(path)?;
+        // so that new clients can be picked up between two readings
+        listener.set_nonblocking(true)?;
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = Arc::clone(&stop);
+        let handle = thread::spawn(move || serve(listener, monitor, interval, thread_stop));
+        Ok(CO2MonitorServer {
+            path: path.to_owned(),
+            stop,
+            handle,
+        })
+    }
+    /// Set this to `true` to make the thread exit after its current reading
+    pub fn stop_flag(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.stop)
+    }
+    /// Stop the thread, wait for it to exit and remove the socket file
+    pub fn stop(self) -> Result<(), CO2MonitorError> {
+        self.stop.store(true, Ordering::SeqCst);
+        // the thread only panics if a callback in it panicked, and there are none
+        let _ = self.handle.join();
+        fs::remove_file(&self.path)?;
+        Ok(())
+    }
+}
+
+fn serve(listener: UnixListener, mut monitor: CO2Monitor, interval: Duration, stop: Arc<AtomicBool>) {
+    let mut clients : Vec<UnixStream> = vec![];
+    let mut latest : Option<String> = None;
+    while !stop.load(Ordering::SeqCst) {
+        while let Ok((mut client, _)) = listener.accept() {
+            // the listener is non-blocking but the clients shouldn't be
+            if client.set_nonblocking(false).is_err() || client.set_write_timeout(Some(CLIENT_WRITE_TIMEOUT)).is_err() {
+                continue;
+            }
+            if let Some(line) = &latest {
+                if client.write_all(line.as_bytes()).is_err() {
+                    continue;
+                }
+            }
+            clients.push(client);
+        }
+        if let Ok(reading) = monitor.read_data(DEFAULT_MAX_REQUESTS) {
+            let line = format!("{}\n", reading_to_value(&reading));
+            // drop the clients that went away
+            clients.retain_mut(|client| client.write_all(line.as_bytes()).is_ok());
+            latest = Some(line);
+        }
+        thread::sleep(interval);
+    }
+}
+
+/// Reads the readings sent by a [CO2MonitorServer]
+pub struct CO2MonitorClient {
+    reader : BufReader<UnixStream>,
+    // a line that has only partly arrived
+    pending : String,
+}
+
+impl CO2MonitorClient {
+    pub fn connect(path: &Path) -> Result<CO2MonitorClient, CO2MonitorError> {
+        Ok(CO2MonitorClient {
+            reader: BufReader::new(UnixStream::connect(path)?),
+            pending: String::new(),
+        })
+    }
+    /// The newest reading the server has sent.
+    ///
+    /// Waits for the next reading if none arrived since the last call, otherwise skips all but
+    /// the newest one that is waiting.
+    pub fn read_latest(&mut self) -> Result<CO2Reading, CO2MonitorError> {
+        self.reader.get_ref().set_nonblocking(false)?;
+        let mut latest = self.read_line()?;
+        self.reader.get_ref().set_nonblocking(true)?;
+        let drained = loop {
+            match self.read_line() {
+                Ok(line) => latest = line,
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break Ok(()),
+                Err(e) => break Err(e),
+            }
+        };
+        self.reader.get_ref().set_nonblocking(false)?;
+        drained?;
+        let value = serde_json::from_str(&latest)
+            .map_err(|e| CO2MonitorError::InvalidReading(e.to_string()))?;
+        value_to_reading(&value)
+    }
+    // the next complete line. A partial line is kept in `pending` if the read would block
+    fn read_line(&mut self) -> io::Result<String> {
+        if self.reader.read_line(&mut self.pending)? == 0 {
+            return Err(io::Error::new(ErrorKind::UnexpectedEof, "The server closed the connection"));
+        }
+        if !self.pending.ends_with('\n') {
+            // only happens if the server closed the connection in the middle of a line
+            return Err(io::Error::new(ErrorKind::UnexpectedEof, "The server closed the connection"));
+        }
+        Ok(std::mem::take(&mut self.pending))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn client_skips_to_latest() {
+        let path = std::env::temp_dir().join(format!("co2meter-rs-test-{}.sock", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+        let mut client = CO2MonitorClient::connect(&path).unwrap();
+        let (mut server, _) = listener.accept().unwrap();
+        server.write_all(b"{\"co2_ppm\":800,\"temp_c\":21.5}\n{\"co2_ppm\":810,\"temp_c\":21.5}\n").unwrap();
+        assert_eq!(client.read_latest().unwrap().co2_ppm.get(), 810);
+        // a line that arrives in two parts
+        server.write_all(b"{\"co2_ppm\":820,").unwrap();
+        server.flush().unwrap();
+        let writer = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            server.write_all(b"\"temp_c\":21.5}\n").unwrap();
+            server
+        });
+        assert_eq!(client.read_latest().unwrap().co2_ppm.get(), 820);
+        drop(writer.join().unwrap());
+        assert!(client.read_latest().is_err());
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,40 @@
+use std::error::Error;
+
+use crate::{CO2Monitor, CO2MonitorError, CO2Reading};
+
+/// An open connection to a [CO2Monitor], see [CO2Monitor::open_for_reading]
+///
+/// The device is closed when the session is dropped.
+///
+/// ```ignore
+/// let mut co2 = CO2Monitor::default()?;
+/// let mut session = co2.open_for_reading()?;
+/// loop {
+///     dbg!(session.read_data(50)?);
+/// }
+/// ```
+pub struct CO2MonitorSession<'a> {
+    pub(crate) monitor : &'a mut CO2Monitor,
+}
+
+impl<'a> CO2MonitorSession<'a> {
+    pub(crate) fn open(monitor: &'a mut CO2Monitor) -> Result<CO2MonitorSession<'a>, Box<dyn Error>> {
+        monitor.hid_open_for_reading()?;
+        Ok(CO2MonitorSession { monitor })
+    }
+    /// Same as [CO2Monitor::read_data] without opening and closing the device
+    pub fn read_data(&mut self, max_requests: u32) -> Result<CO2Reading, Box<dyn Error>> {
+        self.monitor.read_data_inner(max_requests, None)
+    }
+    /// Same as [CO2Monitor::flush]
+    pub fn flush(&mut self) -> Result<u32, CO2MonitorError> {
+        self.monitor.flush()
+    }
+}
+
+impl Drop for CO2MonitorSession<'_> {
+    fn drop(&mut self) {
+        // can only fail if the device is already closed
+        let _ = self.monitor.hid_close();
+    }
+}
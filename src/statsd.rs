@@ -0,0 +1,56 @@
+use std::net::{SocketAddr, UdpSocket};
+
+use log::warn;
+
+use crate::{CO2MonitorError, CO2Reading};
+
+/// Sends readings to a StatsD server as gauges over UDP, see [CO2Reading::to_statsd_gauge_lines]
+///
+/// StatsD is fire and forget: a reading that can't be sent is dropped and logged with
+/// `log::warn!`, it never fails the caller.
+///
+/// ```ignore
+/// let emitter = CO2MonitorStatsdEmitter::new("127.0.0.1:8125".parse()?, "office".to_owned())?;
+/// emitter.emit(&co2.read_data(50)?);
+/// ```
+pub struct CO2MonitorStatsdEmitter {
+    socket : UdpSocket,
+    addr : SocketAddr,
+    prefix : String,
+}
+
+impl CO2MonitorStatsdEmitter {
+    /// Fails if no local UDP socket could be bound
+    pub fn new(addr: SocketAddr, prefix: String) -> Result<CO2MonitorStatsdEmitter, CO2MonitorError> {
+        let local : SocketAddr = if addr.is_ipv4() { ([0, 0, 0, 0], 0).into() } else { ([0_u16; 8], 0).into() };
+        let socket = UdpSocket::bind(local)?;
+        // a full send buffer drops the reading instead of stalling the caller
+        socket.set_nonblocking(true)?;
+        Ok(CO2MonitorStatsdEmitter { socket, addr, prefix })
+    }
+    /// Send the gauges of `reading` in one packet
+    pub fn emit(&self, reading: &CO2Reading) {
+        let lines = reading.to_statsd_gauge_lines(&self.prefix);
+        if let Err(e) = self.socket.send_to(lines.as_bytes(), self.addr) {
+            warn!("Unable to send a reading to StatsD at {}: {}", self.addr, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use crate::reading;
+
+    #[test]
+    fn sends_gauges() {
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        server.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        let emitter = CO2MonitorStatsdEmitter::new(server.local_addr().unwrap(), "office".to_owned()).unwrap();
+        emitter.emit(&reading(812, 21.5));
+        let mut buf = [0_u8; 128];
+        let len = server.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..len], b"office.co2_ppm:812|g\noffice.temp_c:21.5|g");
+    }
+}
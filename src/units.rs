@@ -0,0 +1,153 @@
+use std::fmt;
+
+#[cfg(feature="serde")]
+use serde::{Serialize, Deserialize};
+
+use crate::CO2MonitorError;
+
+// There can't be more than a million parts per million
+const MAX_CO2_PPM : u32 = 1_000_000;
+const ABSOLUTE_ZERO_C : f32 = -273.15;
+
+/// A CO2 concentration in parts per million.
+///
+/// Use `CO2PPM::try_from(u32)` to build one (values above 1,000,000 ppm are rejected) and
+/// `u32::from(..)` or [CO2PPM::get] to get the number back out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(try_from = "u32", into = "u32"))]
+pub struct CO2PPM(u32);
+
+impl CO2PPM {
+    /// The concentration as a plain `u32`
+    pub fn get(self) -> u32 {
+        self.0
+    }
+    /// `self + rhs`. Fails with [CO2MonitorError::InvalidReading] above 1,000,000 ppm
+    pub fn checked_add(self, rhs: CO2PPM) -> Result<CO2PPM, CO2MonitorError> {
+        // both are at most MAX_CO2_PPM, so this can't overflow
+        CO2PPM::try_from(self.0 + rhs.0)
+    }
+    /// `self - rhs`. Fails with [CO2MonitorError::InvalidReading] if `rhs` is larger
+    pub fn checked_sub(self, rhs: CO2PPM) -> Result<CO2PPM, CO2MonitorError> {
+        self.0.checked_sub(rhs.0)
+            .map(CO2PPM)
+            .ok_or_else(|| CO2MonitorError::InvalidReading(format!("{} is less than {}", self, rhs)))
+    }
+}
+
+impl TryFrom<u32> for CO2PPM {
+    type Error = CO2MonitorError;
+
+    fn try_from(ppm: u32) -> Result<Self, Self::Error> {
+        if ppm > MAX_CO2_PPM {
+            return Err(CO2MonitorError::InvalidReading(format!("{} ppm is above {} ppm", ppm, MAX_CO2_PPM)));
+        }
+        Ok(CO2PPM(ppm))
+    }
+}
+
+impl From<CO2PPM> for u32 {
+    fn from(ppm: CO2PPM) -> Self {
+        ppm.0
+    }
+}
+
+impl fmt::Display for CO2PPM {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ppm", self.0)
+    }
+}
+
+/// A temperature in degrees celcius.
+///
+/// Use `TempCelsius::try_from(f32)` to build one (NaN, infinities and values below absolute zero
+/// are rejected) and `f32::from(..)` or [TempCelsius::get] to get the number back out.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(try_from = "f32", into = "f32"))]
+pub struct TempCelsius(f32);
+
+impl TempCelsius {
+    /// The temperature as a plain `f32`
+    pub fn get(self) -> f32 {
+        self.0
+    }
+    /// This temperature raised by `delta_c` degrees. Fails with
+    /// [CO2MonitorError::InvalidReading] if the result isn't a valid temperature
+    pub fn checked_add(self, delta_c: f32) -> Result<TempCelsius, CO2MonitorError> {
+        TempCelsius::try_from(self.0 + delta_c)
+    }
+    /// This temperature lowered by `delta_c` degrees. Fails with
+    /// [CO2MonitorError::InvalidReading] if the result is below absolute zero
+    pub fn checked_sub(self, delta_c: f32) -> Result<TempCelsius, CO2MonitorError> {
+        TempCelsius::try_from(self.0 - delta_c)
+    }
+}
+
+impl TryFrom<f32> for TempCelsius {
+    type Error = CO2MonitorError;
+
+    fn try_from(temp_c: f32) -> Result<Self, Self::Error> {
+        if !temp_c.is_finite() || temp_c < ABSOLUTE_ZERO_C {
+            return Err(CO2MonitorError::InvalidReading(format!("{}°C is not a valid temperature", temp_c)));
+        }
+        Ok(TempCelsius(temp_c))
+    }
+}
+
+impl From<TempCelsius> for f32 {
+    fn from(temp: TempCelsius) -> Self {
+        temp.0
+    }
+}
+
+/// Respects the precision of the formatter, so `format!("{:.1}", temp)` gives `"21.3°C"`
+impl fmt::Display for TempCelsius {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match f.precision() {
+            Some(precision) => write!(f, "{:.*}°C", precision, self.0),
+            None => write!(f, "{}°C", self.0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn co2_range() {
+        assert_eq!(CO2PPM::try_from(0).unwrap().get(), 0);
+        assert_eq!(u32::from(CO2PPM::try_from(1_000_000).unwrap()), 1_000_000);
+        assert!(CO2PPM::try_from(1_000_001).is_err());
+    }
+
+    #[test]
+    fn temp_range() {
+        assert_eq!(TempCelsius::try_from(-273.15).unwrap().get(), -273.15);
+        assert!(TempCelsius::try_from(-274.0).is_err());
+        assert!(TempCelsius::try_from(f32::NAN).is_err());
+        assert!(TempCelsius::try_from(f32::INFINITY).is_err());
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(CO2PPM::try_from(812).unwrap().to_string(), "812 ppm");
+        assert_eq!(format!("{:.1}", TempCelsius::try_from(21.3125).unwrap()), "21.3°C");
+        assert_eq!(TempCelsius::try_from(21.5).unwrap().to_string(), "21.5°C");
+    }
+
+    #[test]
+    fn arithmetic() {
+        let a = CO2PPM::try_from(900).unwrap();
+        let b = CO2PPM::try_from(400).unwrap();
+        assert_eq!(a.checked_sub(b).unwrap().get(), 500);
+        assert_eq!(a.checked_add(b).unwrap().get(), 1300);
+        assert!(b.checked_sub(a).is_err());
+        assert!(CO2PPM::try_from(1_000_000).unwrap().checked_add(b).is_err());
+        let t = TempCelsius::try_from(21.5).unwrap();
+        assert_eq!(t.checked_sub(1.5).unwrap().get(), 20.0);
+        assert_eq!(t.checked_add(1.5).unwrap().get(), 23.0);
+        assert!(t.checked_sub(300.0).is_err());
+        assert!(t.checked_add(f32::NAN).is_err());
+    }
+}
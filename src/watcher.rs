@@ -0,0 +1,232 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+#[cfg(feature="serde")]
+use serde::{Serialize, Deserialize};
+
+use crate::{CO2Monitor, CO2Reading, DEFAULT_MAX_REQUESTS};
+
+/// Settings for a [CO2MonitorWatcher]
+#[derive(Debug, Clone)]
+pub struct WatcherConfig {
+    /// Time to wait between two readings
+    pub poll_interval : Duration,
+    /// co2 at or above this is [ThresholdStatus::Warning]
+    pub warn_ppm : u32,
+    /// co2 at or above this is [ThresholdStatus::Critical]
+    pub critical_ppm : u32,
+    /// How far the co2 has to fall below a threshold before the status goes back down. This stops
+    /// the status from flapping when the co2 hovers around a threshold.
+    pub hysteresis_ppm : u32,
+    /// Passed to [CO2Monitor::read_data]
+    pub max_requests : u32,
+}
+
+impl Default for WatcherConfig {
+    fn default() -> Self {
+        WatcherConfig {
+            poll_interval: Duration::from_secs(10),
+            warn_ppm: 1000,
+            critical_ppm: 2000,
+            hysteresis_ppm: 50,
+            max_requests: DEFAULT_MAX_REQUESTS,
+        }
+    }
+}
+
+impl WatcherConfig {
+    // the status for `co2_ppm` given that the status was `previous`
+    fn status_for(&self, previous: ThresholdStatus, co2_ppm: u32) -> ThresholdStatus {
+        let rising = self.status_without_hysteresis(co2_ppm);
+        if rising >= previous {
+            return rising;
+        }
+        // only go down once we are far enough below the threshold
+        self.status_without_hysteresis(co2_ppm.saturating_add(self.hysteresis_ppm)).min(previous)
+    }
+    fn status_without_hysteresis(&self, co2_ppm: u32) -> ThresholdStatus {
+        if co2_ppm >= self.critical_ppm {
+            ThresholdStatus::Critical
+        } else if co2_ppm >= self.warn_ppm {
+            ThresholdStatus::Warning
+        } else {
+            ThresholdStatus::Normal
+        }
+    }
+}
+
+/// How the co2 compares to the thresholds of a [WatcherConfig]
+///
+/// If you enable the `serde` feature then this also derives Serialize and Deserialize
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ThresholdStatus {
+    Normal,
+    Warning,
+    Critical,
+}
+
+/// Sent by a [CO2MonitorWatcher] when the [ThresholdStatus] changes
+///
+/// If you enable the `serde` feature then this also derives Serialize and Deserialize
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ThresholdEvent {
+    pub previous : ThresholdStatus,
+    pub current : ThresholdStatus,
+    /// The reading that caused the change
+    pub reading : CO2Reading,
+}
+
+/// Which way the co2 crossed the limit of a [ThresholdWatcher]
+///
+/// If you enable the `serde` feature then this also derives Serialize and Deserialize
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ThresholdCrossing {
+    /// The co2 went up to or above the limit
+    Rising,
+    /// The co2 went back down below the limit minus the hysteresis
+    Falling,
+}
+/// Tells you when the co2 crosses a single limit, for driving an alarm. 
+///
+/// Unlike [CO2MonitorWatcher] this doesn't read the device, you feed it the readings yourself.
+///
+/// ```ignore
+/// let mut alarm = ThresholdWatcher::new(1500, 50);
+/// match alarm.update(&co2.read_data(50)?) {
+///     Some(ThresholdCrossing::Rising) => buzzer_on(),
+///     Some(ThresholdCrossing::Falling) => buzzer_off(),
+///     None => {},
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ThresholdWatcher {
+    limit_ppm : u32,
+    hysteresis_ppm : u32,
+    above : bool,
+}
+impl ThresholdWatcher {
+    /// Once the co2 reached `limit_ppm` it has to fall below `limit_ppm - hysteresis_ppm` before
+    /// [ThresholdCrossing::Falling] is returned. Starts out below the limit.
+    pub fn new(limit_ppm: u32, hysteresis_ppm: u32) -> ThresholdWatcher {
+        ThresholdWatcher {
+            limit_ppm,
+            hysteresis_ppm,
+            above: false,
+        }
+    }
+    /// Returns `Some(..)` only for the reading that crosses the limit
+    pub fn update(&mut self, reading: &CO2Reading) -> Option<ThresholdCrossing> {
+        let co2_ppm = reading.co2_ppm.get();
+        if !self.above && co2_ppm >= self.limit_ppm {
+            self.above = true;
+            return Some(ThresholdCrossing::Rising);
+        }
+        if self.above && co2_ppm.saturating_add(self.hysteresis_ppm) < self.limit_ppm {
+            self.above = false;
+            return Some(ThresholdCrossing::Falling);
+        }
+        None
+    }
+    /// Whether the co2 is currently considered above the limit
+    pub fn is_above(&self) -> bool {
+        self.above
+    }
+}
+/// Polls a [CO2Monitor] on a background thread and calls you back. 
+///
+/// ```ignore
+/// let watcher = CO2MonitorWatcher::new(CO2Monitor::default()?, WatcherConfig::default());
+/// let stop = watcher.stop_flag();
+/// let handle = watcher.start(
+///     |reading| println!("{:?}", reading),
+///     |event| println!("co2 is now {:?}", event.current),
+/// );
+/// // later
+/// stop.store(true, Ordering::SeqCst);
+/// handle.join().unwrap();
+/// ```
+pub struct CO2MonitorWatcher {
+    monitor : CO2Monitor,
+    config : WatcherConfig,
+    stop : Arc<AtomicBool>,
+}
+
+impl CO2MonitorWatcher {
+    pub fn new(monitor: CO2Monitor, config: WatcherConfig) -> CO2MonitorWatcher {
+        CO2MonitorWatcher {
+            monitor,
+            config,
+            stop: Arc::new(AtomicBool::new(false)),
+        }
+    }
+    /// Set this to `true` to make the thread exit after its current poll
+    pub fn stop_flag(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.stop)
+    }
+    /// Start polling on a new thread. 
+    ///
+    /// `on_reading` is called with every successful reading and `on_threshold` every time the
+    /// [ThresholdStatus] changes. The status starts out as [ThresholdStatus::Normal]. Failed
+    /// reads are skipped.
+    pub fn start<F, G>(self, on_reading: F, on_threshold: G) -> JoinHandle<()>
+    where
+        F: Fn(CO2Reading) + Send + 'static,
+        G: Fn(ThresholdEvent) + Send + 'static,
+    {
+        let CO2MonitorWatcher { mut monitor, config, stop } = self;
+        thread::spawn(move || {
+            let mut status = ThresholdStatus::Normal;
+            while !stop.load(Ordering::SeqCst) {
+                if let Ok(reading) = monitor.read_data(config.max_requests) {
+                    let current = config.status_for(status, reading.co2_ppm.get());
+                    if current != status {
+                        on_threshold(ThresholdEvent { previous: status, current, reading: reading.clone() });
+                        status = current;
+                    }
+                    on_reading(reading);
+                }
+                thread::sleep(config.poll_interval);
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reading;
+    use ThresholdStatus::*;
+    use ThresholdCrossing::*;
+
+    #[test]
+    fn status_with_hysteresis() {
+        let config = WatcherConfig {
+            warn_ppm: 1000,
+            critical_ppm: 2000,
+            hysteresis_ppm: 50,
+            ..WatcherConfig::default()
+        };
+        let mut status = Normal;
+        let mut seen = vec![];
+        for ppm in [900, 1000, 960, 949, 2100, 1960, 1000, 940] {
+            status = config.status_for(status, ppm);
+            seen.push(status);
+        }
+        assert_eq!(seen, vec![Normal, Warning, Warning, Normal, Critical, Critical, Warning, Normal]);
+    }
+
+    #[test]
+    fn crossings_only_on_edges() {
+        let mut alarm = ThresholdWatcher::new(1000, 50);
+        let crossings : Vec<Option<ThresholdCrossing>> = [900, 1000, 1100, 960, 949, 800, 1000]
+            .into_iter()
+            .map(|ppm| alarm.update(&reading(ppm, 20.0)))
+            .collect();
+        assert_eq!(crossings, vec![None, Some(Rising), None, None, Some(Falling), None, Some(Rising)]);
+    }
+}